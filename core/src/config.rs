@@ -0,0 +1,148 @@
+use std::error::Error;
+use std::fmt;
+
+use super::game::{CollisionResponse, FlightModel, Tiebreak};
+use super::health::ShieldConfig;
+use super::ray_casting::RayCastBackend;
+use super::sensors::{RadarConfig, SensorNoise};
+use super::spawn::SpawnConfig;
+use super::weapon::WeaponConfig;
+
+const DEFAULT_TIME_STEP: f64 = 0.1;
+const DEFAULT_MAX_SUBSTEPS: usize = 10000;
+const DEFAULT_PLAYER_RADIUS: f64 = 10.0;
+const DEFAULT_PLAYER_MAX_SPEED: f64 = 960.0;
+const DEFAULT_PLAYER_HP: f64 = 1.0;
+const DEFAULT_MISSILE_DAMAGE: f64 = 1.0;
+const DEFAULT_MISSILE_SPEED: f64 = 2880.0;
+const DEFAULT_MISSILE_RADIUS: f64 = 2.0;
+
+/// Why a game config can't be used
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ConfigError {
+    /// The time step isn't positive
+    TimeStep(f64),
+    /// Max amount of substeps is zero
+    MaxSubsteps,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::TimeStep(t) => write!(f, "time step must be positive, got {}", t),
+            ConfigError::MaxSubsteps => write!(f, "max substeps must be positive"),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Simulation settings shared by every player of a game
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameConfig {
+    /// Fixed step of the simulation in seconds, `process` never integrates more than this at once
+    pub time_step: f64,
+    /// Max amount of steps per `process` call, the time left is carried over to the next call
+    pub max_substeps: usize,
+    pub collision_response: CollisionResponse,
+    /// Whether missiles and rams hurt players of the same team
+    pub friendly_fire: bool,
+    pub player_radius: f64,
+    pub player_max_speed: f64,
+    pub player_hp: f64,
+    /// Shield of every ship, ships have no shield if it's not set
+    pub shield: Option<ShieldConfig>,
+    pub missile_speed: f64,
+    pub missile_damage: f64,
+    /// Max distance a missile flies, unlimited if it's not set
+    pub missile_range: Option<f64>,
+    /// Max time a missile lives in seconds, unlimited if it's not set
+    pub missile_ttl: Option<f64>,
+    /// Whether missiles of different players destroy each other on contact
+    pub missile_interception: bool,
+    /// Radius of missiles used for interception and sensors, missiles are points otherwise
+    pub missile_radius: f64,
+    /// Whether rays of players see missiles of other players
+    pub missiles_visible: bool,
+    /// Noise of the rays of every player, rays are perfect if it's not set
+    pub sensor_noise: Option<SensorNoise>,
+    /// Radar of every player, players have no radar if it's not set
+    pub radar: Option<RadarConfig>,
+    /// Max game time in seconds, the match is decided by `tiebreak` when it expires
+    pub time_limit: Option<f64>,
+    pub tiebreak: Tiebreak,
+    /// Seed of the game's RNG, a random one is used if it's not set
+    pub seed: Option<u64>,
+    pub ray_cast_backend: RayCastBackend,
+    pub flight_model: FlightModel,
+    pub weapon: WeaponConfig,
+    pub spawn: SpawnConfig,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            time_step: DEFAULT_TIME_STEP,
+            max_substeps: DEFAULT_MAX_SUBSTEPS,
+            collision_response: CollisionResponse::Block,
+            friendly_fire: false,
+            player_radius: DEFAULT_PLAYER_RADIUS,
+            player_max_speed: DEFAULT_PLAYER_MAX_SPEED,
+            player_hp: DEFAULT_PLAYER_HP,
+            shield: None,
+            missile_speed: DEFAULT_MISSILE_SPEED,
+            missile_damage: DEFAULT_MISSILE_DAMAGE,
+            missile_range: None,
+            missile_ttl: None,
+            missile_interception: false,
+            missile_radius: DEFAULT_MISSILE_RADIUS,
+            missiles_visible: false,
+            sensor_noise: None,
+            radar: None,
+            time_limit: None,
+            tiebreak: Tiebreak::Draw,
+            seed: None,
+            ray_cast_backend: RayCastBackend::Analytic,
+            flight_model: FlightModel::Arcade,
+            weapon: WeaponConfig::default(),
+            spawn: SpawnConfig::default(),
+        }
+    }
+}
+
+impl GameConfig {
+    /// Checks the settings the simulation can't run with
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.time_step.is_nan() || self.time_step <= 0.0 {
+            return Err(ConfigError::TimeStep(self.time_step));
+        }
+        if self.max_substeps == 0 {
+            return Err(ConfigError::MaxSubsteps);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigError, GameConfig};
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(GameConfig::default().validate(), Ok(()));
+
+        for time_step in [0.0, -0.1, f64::NAN] {
+            let config = GameConfig {
+                time_step,
+                ..Default::default()
+            };
+            assert!(matches!(config.validate(), Err(ConfigError::TimeStep(_))));
+        }
+
+        let config = GameConfig {
+            max_substeps: 0,
+            ..Default::default()
+        };
+        assert_eq!(config.validate(), Err(ConfigError::MaxSubsteps));
+    }
+}
@@ -0,0 +1,91 @@
+/// Something that happened in a game. Positions are where it happened, ids are the ids of
+/// players and missiles.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    ShotFired {
+        missile_id: usize,
+        player_id: usize,
+        x: f64,
+        y: f64,
+        direction: f64,
+    },
+    /// The missile runs out of range or lifetime
+    MissileExpired {
+        missile_id: usize,
+        x: f64,
+        y: f64,
+    },
+    MissileHitBorder {
+        missile_id: usize,
+        x: f64,
+        y: f64,
+    },
+    MissileHitBarrier {
+        missile_id: usize,
+        /// Index of the barrier in `map.barriers`
+        barrier: usize,
+        x: f64,
+        y: f64,
+    },
+    /// Two missiles destroy each other
+    MissileIntercepted {
+        missile_id: usize,
+        other_id: usize,
+        x: f64,
+        y: f64,
+    },
+    PlayerHit {
+        missile_id: usize,
+        attacker_id: usize,
+        player_id: usize,
+        damage: f64,
+        x: f64,
+        y: f64,
+    },
+    PlayerKilled {
+        player_id: usize,
+        killer_id: usize,
+        x: f64,
+        y: f64,
+    },
+    /// The moving player touches the other one
+    ShipCollision {
+        player_id: usize,
+        other_id: usize,
+        x: f64,
+        y: f64,
+    },
+    /// The winning team is not set if the match is a draw
+    GameOver {
+        winner_team: Option<usize>,
+    },
+}
+
+/// An event with the game time it happened at
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameEvent {
+    pub time: f64,
+    pub event: Event,
+}
+
+/// Buffer of events shared by the game and its players. It grows until the events are drained,
+/// so callers are expected to drain it regularly.
+#[derive(Clone, Debug, Default)]
+pub struct EventLog {
+    /// Current game time, it's used for events recorded between steps
+    pub time: f64,
+    pub events: Vec<GameEvent>,
+}
+
+impl EventLog {
+    pub fn push(&mut self, event: Event) {
+        self.events.push(GameEvent {
+            time: self.time,
+            event,
+        });
+    }
+
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
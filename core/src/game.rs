@@ -1,34 +1,149 @@
 use std::f64::consts::PI;
-use std::sync::{Arc, Mutex};
+use std::ops::DerefMut;
+use std::sync::{Arc, Mutex, MutexGuard};
 
-use super::map::Map;
-use super::player::{Missile, Player, PlayerStatus};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
-const TIME_STEP: f64 = 0.1;
+use super::config::GameConfig;
+use super::events::{Event, EventLog, GameEvent};
+use super::geometry::{segment_circle_intersection, segment_rect_exit, Circle};
+use super::map::{Map, SpawnPoint};
+use super::player::{IdAllocator, Missile, Player, PlayerStatus, PlayerTrait};
+use super::spatial::SpatialGrid;
+use super::spawn::{allocate, SpawnError};
 
+const DEFAULT_NEWTONIAN_ACCELERATION: f64 = 1920.0;
+const DEFAULT_NEWTONIAN_MAX_ANGULAR_VELOCITY: f64 = 360.0;
+const DEFAULT_NEWTONIAN_DRAG: f64 = 0.5;
+
+// Game time is summed up by steps, so it may stop a bit below the time limit
+const TIME_LIMIT_EPSILON: f64 = 1e-9;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum GameStatus {
     On,
-    Over(Arc<Mutex<Player>>),
+    /// Only players of this team are alive
+    Over(usize),
     OverDraw,
 }
 
+/// What happens when two ships touch each other
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CollisionResponse {
+    /// The moving ship stops the same way it stops at barriers
+    Block,
+    /// The moving ship stops and its direction is reflected by the contact normal
+    Bounce,
+    /// The ship with the higher closing speed destroys the other one, equal speeds destroy both
+    Ram,
+}
+
+/// How the match is decided when the time limit expires. Only teams with alive players take part,
+/// the score of a team is summed up over all its players.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Tiebreak {
+    /// The team with the most kills wins
+    Kills,
+    /// The team with the most damage dealt wins
+    Damage,
+    /// The match is a draw
+    Draw,
+}
+
+/// Settings of the Newtonian flight model
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NewtonianConfig {
+    /// Acceleration at the full thrust
+    pub acceleration: f64,
+    /// Angular velocity at the full turn in degrees per second
+    pub max_angular_velocity: f64,
+    /// Velocity loss rate per second, the velocity decays as `exp(-drag * t)` without thrust
+    pub drag: f64,
+}
+
+impl Default for NewtonianConfig {
+    fn default() -> Self {
+        NewtonianConfig {
+            acceleration: DEFAULT_NEWTONIAN_ACCELERATION,
+            max_angular_velocity: DEFAULT_NEWTONIAN_MAX_ANGULAR_VELOCITY,
+            drag: DEFAULT_NEWTONIAN_DRAG,
+        }
+    }
+}
+
+/// How player commands move ships
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FlightModel {
+    /// `rotate` and `set_speed` take effect instantly
+    Arcade,
+    /// Ships have inertia and are driven by `set_thrust` and `set_turn`
+    Newtonian(NewtonianConfig),
+}
+
+/// Game shared with its players, `World` runs the same simulation over players it owns
 pub struct Game {
     pub map: Map,
+    pub config: GameConfig,
     pub players: Vec<Arc<Mutex<Player>>>,
     pub missiles: Arc<Mutex<Vec<Missile>>>,
+    /// Events recorded by the game and its players
+    pub events: Arc<Mutex<EventLog>>,
     pub status: GameStatus,
+    /// Seed of the game's RNG used for spawns and random directions
+    pub seed: u64,
+    pub rng: StdRng,
+    pub ids: Arc<IdAllocator>,
+    /// Elapsed game time in seconds
+    pub time: f64,
+    // Time passed to `process` but not simulated yet
+    pub(crate) pending_time: f64,
+    // Broad phase index of `map.barriers`
+    pub(crate) barriers_index: SpatialGrid,
 }
 
 impl Game {
-    pub fn new(map: Map) -> Arc<Mutex<Self>> {
+    pub fn new(map: Map, config: GameConfig) -> Arc<Mutex<Self>> {
+        let seed = config.seed.unwrap_or_else(rand::random::<u64>);
         let game = Game {
+            barriers_index: map.build_index(),
             map,
+            config,
             players: Vec::new(),
             missiles: Arc::new(Mutex::new(Vec::new())),
+            events: Arc::new(Mutex::new(EventLog::default())),
             status: GameStatus::On,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            ids: Arc::new(IdAllocator::new()),
+            time: 0.0,
+            pending_time: 0.0,
         };
         Arc::new(Mutex::new(game))
     }
+
+    /// Must be called after `map.barriers` are changed
+    pub fn rebuild_index(&mut self) {
+        self.barriers_index = self.map.build_index();
+    }
+
+    /// Takes the events recorded since the last call
+    pub fn drain_events(&self) -> Vec<GameEvent> {
+        self.events.lock().unwrap().drain()
+    }
+
+    /// Finds a point free of barriers using the game's RNG
+    pub fn get_free_point(&mut self, r: f64) -> Result<(f64, f64), SpawnError> {
+        self.map.get_free_point(r, &mut self.rng)
+    }
+
+    /// Places a new ship of radius `r` following `config.spawn`, the map's spawn points are
+    /// taken first
+    pub fn spawn_point(&mut self, r: f64) -> Result<SpawnPoint, SpawnError> {
+        let players: Vec<_> = self.players.iter().map(|p| p.lock().unwrap()).collect();
+        let ships: Vec<&Player> = players.iter().map(|p| &**p).collect();
+        allocate(&self.map, &ships, r, &self.config, &mut self.rng)
+    }
 }
 
 pub trait GameTrait {
@@ -37,127 +152,682 @@ pub trait GameTrait {
 
 pub trait RegisterPlayer {
     fn register_player(self: &Arc<Self>, player: &Arc<Mutex<Player>>);
+    /// Registers the player at the place picked by `Game::spawn_point`, the player is left
+    /// unregistered if there's no room
+    fn spawn_player(self: &Arc<Self>, player: &Arc<Mutex<Player>>) -> Result<(), SpawnError>;
 }
 
-impl GameTrait for Game {
-    fn process(&mut self, time: f64) {
-        let Game {
-            ref map,
-            ref mut missiles,
-            ref mut players,
-            ref mut status,
-        } = *self;
+/// Parts of a game that stay the same during a step
+#[derive(Copy, Clone)]
+pub(crate) struct Arena<'a> {
+    pub map: &'a Map,
+    pub barriers_index: &'a SpatialGrid,
+    pub config: &'a GameConfig,
+}
+
+/// Simulates the pending time by fixed steps. `step` gets the time delta and the game time at
+/// the end of a step and returns whether the game goes on.
+pub(crate) fn run_steps(
+    config: &GameConfig,
+    time: &mut f64,
+    pending_time: &mut f64,
+    mut step: impl FnMut(f64, f64) -> bool,
+) {
+    let mut substeps = 0;
+    while *pending_time > 0.0 && substeps < config.max_substeps {
+        let mut timedelta = pending_time.min(config.time_step);
+        if let Some(time_limit) = config.time_limit {
+            // Don't step over the time limit
+            timedelta = timedelta.min((time_limit - *time).max(0.0));
+        }
+        *pending_time -= timedelta;
+        *time += timedelta;
+        substeps += 1;
+
+        if !step(timedelta, *time) {
+            // The time left doesn't matter once the game is over
+            *pending_time = 0.0;
+            break;
+        }
+    }
+}
+
+/// Simulates a step that ends at `time`. Events of the step happen at its end unless their
+/// moment is known exactly.
+pub(crate) fn step<P: DerefMut<Target = Player>>(
+    arena: &Arena,
+    players: &mut [P],
+    missiles: &mut Vec<Missile>,
+    status: &mut GameStatus,
+    time: f64,
+    timedelta: f64,
+    events: &mut Vec<GameEvent>,
+) {
+    simulate(arena, players, missiles, status, time, timedelta, events);
+    if *status == GameStatus::On
+        && matches!(arena.config.time_limit, Some(t) if time >= t - TIME_LIMIT_EPSILON)
+    {
+        *status = finish_by_tiebreak(players, arena.config, time, events);
+    }
+}
 
-        let mut time_left = time;
-        let mut timedelta: f64;
-        loop {
-            if time_left > TIME_STEP {
-                timedelta = TIME_STEP;
-            } else if time_left <= TIME_STEP && time_left > 0.0 {
-                timedelta = time_left;
-            } else {
+/// Decides the match when the time limit expires and returns the final status
+fn finish_by_tiebreak<P: DerefMut<Target = Player>>(
+    players: &mut [P],
+    config: &GameConfig,
+    time: f64,
+    events: &mut Vec<GameEvent>,
+) -> GameStatus {
+    let mut teams: Vec<(usize, f64)> = Vec::new();
+    for player in players.iter() {
+        let team = player.get_team();
+        if player.status == PlayerStatus::InGame && !teams.iter().any(|&(t, _)| t == team) {
+            teams.push((team, 0.0));
+        }
+    }
+    for player in players.iter() {
+        let score = match config.tiebreak {
+            Tiebreak::Kills => player.kills as f64,
+            Tiebreak::Damage => player.damage_dealt,
+            Tiebreak::Draw => 0.0,
+        };
+        if let Some(entry) = teams.iter_mut().find(|(t, _)| *t == player.get_team()) {
+            entry.1 += score;
+        }
+    }
+
+    // The best team wins only if nobody shares its score
+    let best = teams
+        .iter()
+        .map(|&(_, score)| score)
+        .fold(f64::MIN, f64::max);
+    let mut best_teams = teams.iter().filter(|&&(_, score)| score == best);
+    let winner = match (best_teams.next(), best_teams.next()) {
+        (Some(&(team, _)), None) if config.tiebreak != Tiebreak::Draw => Some(team),
+        _ => None,
+    };
+
+    for player in players.iter_mut() {
+        if player.status != PlayerStatus::InGame {
+            continue;
+        }
+        player.status = match winner {
+            Some(team) if player.get_team() == team => PlayerStatus::Win,
+            Some(_) => PlayerStatus::Lose,
+            None => PlayerStatus::Draw,
+        };
+    }
+    events.push(GameEvent {
+        time,
+        event: Event::GameOver {
+            winner_team: winner,
+        },
+    });
+    match winner {
+        Some(team) => GameStatus::Over(team),
+        None => GameStatus::OverDraw,
+    }
+}
+
+/// Moves ships and missiles and resolves their hits
+fn simulate<P: DerefMut<Target = Player>>(
+    arena: &Arena,
+    players: &mut [P],
+    missiles: &mut Vec<Missile>,
+    status: &mut GameStatus,
+    time: f64,
+    timedelta: f64,
+    events: &mut Vec<GameEvent>,
+) {
+    let Arena {
+        map,
+        barriers_index,
+        config,
+    } = *arena;
+    let previous_positions: Vec<(f64, f64)> = players.iter().map(|p| (p.x, p.y)).collect();
+
+    // Broad phase index of players for this step, it's updated as players move
+    let mut players_index = SpatialGrid::from_circles(
+        map.width,
+        map.height,
+        &players.iter().map(|p| p.circle()).collect::<Vec<_>>(),
+    );
+    let mut candidates: Vec<usize> = Vec::new();
+
+    for i in 0..players.len() {
+        let player = &players[i];
+
+        if player.status != PlayerStatus::InGame {
+            continue;
+        }
+
+        players[i].weapon.tick(timedelta);
+        players[i].health.tick(timedelta);
+        let player = &players[i];
+
+        // Calculate velocity by the flight model
+
+        let (mut vx, mut vy) = match config.flight_model {
+            FlightModel::Arcade => velocity(player.direction, player.speed),
+            FlightModel::Newtonian(newtonian) => {
+                let player = &mut players[i];
+                player.direction += player.turn * newtonian.max_angular_velocity * timedelta;
+                let (ax, ay) = velocity(player.direction, player.thrust * newtonian.acceleration);
+                let damping = (-newtonian.drag * timedelta).exp();
+                let vx = (player.vx + ax * timedelta) * damping;
+                let vy = (player.vy + ay * timedelta) * damping;
+                let speed = (vx * vx + vy * vy).sqrt();
+                if speed > player.max_speed {
+                    (vx * player.max_speed / speed, vy * player.max_speed / speed)
+                } else {
+                    (vx, vy)
+                }
+            }
+        };
+        let player = &players[i];
+
+        // Calculate next coordinates
+
+        let mut next_x = player.x + vx * timedelta;
+        let mut next_y = player.y + vy * timedelta;
+
+        // Borders collision detection and handling
+
+        if next_x - player.r < 0.0 {
+            next_x = player.r;
+            vx = 0.0;
+        } else if next_x + player.r > map.width {
+            next_x = map.width - player.r;
+            vx = 0.0;
+        }
+        if next_y - player.r < 0.0 {
+            next_y = player.r;
+            vy = 0.0;
+        } else if next_y + player.r > map.height {
+            next_y = map.height - player.r;
+            vy = 0.0;
+        }
+
+        // Barriers collision detection
+
+        barriers_index.query_circle(next_x, next_y, player.r, &mut candidates);
+        for barrier in candidates.iter().map(|&j| &map.barriers[j]) {
+            if barrier.distance(next_x, next_y) < player.r {
+                // Don't move player if detect collision
+                next_x = player.x;
+                next_y = player.y;
+                (vx, vy) = (0.0, 0.0);
                 break;
             }
-            time_left = ((time_left - TIME_STEP) * 10000.0).round() / 10000.0;
+        }
+
+        // Players collision detection
 
-            let mut alived_players_count = 0;
-            let mut alived_player: Option<Arc<Mutex<Player>>> = None;
-            for player_arc in players.iter() {
-                let mut player = player_arc.lock().unwrap();
+        players_index.query_circle(next_x, next_y, player.r, &mut candidates);
+        let contact = candidates.iter().copied().find(|&j| {
+            let other = &players[j];
+            j != i
+                && other.status == PlayerStatus::InGame
+                && ((next_x - other.x).powi(2) + (next_y - other.y).powi(2)).sqrt()
+                    < (player.r + other.r)
+        });
 
-                if player.status != PlayerStatus::InGame {
-                    continue;
+        if let Some(j) = contact {
+            events.push(GameEvent {
+                time,
+                event: Event::ShipCollision {
+                    player_id: players[i].id,
+                    other_id: players[j].id,
+                    x: next_x,
+                    y: next_y,
+                },
+            });
+            (vx, vy) = resolve_players_collision(players, i, j, (next_x, next_y), (vx, vy), config);
+            // Both players were alive before the contact, so any kill is new
+            for k in [i, j] {
+                let p = &players[k];
+                if let PlayerStatus::KilledBy(killer_id) = p.status {
+                    events.push(GameEvent {
+                        time,
+                        event: Event::PlayerKilled {
+                            player_id: p.id,
+                            killer_id,
+                            x: p.x,
+                            y: p.y,
+                        },
+                    });
                 }
+            }
+            // Don't move player if detect collision
+            let player = &players[i];
+            next_x = player.x;
+            next_y = player.y;
+        }
+
+        let player = &mut players[i];
+        player.vx = vx;
+        player.vy = vy;
+        if let FlightModel::Newtonian(_) = config.flight_model {
+            player.speed = (vx * vx + vy * vy).sqrt();
+        }
+        if (next_x, next_y) != (player.x, player.y) {
+            players_index.remove(i, &player.circle());
+            player.x = next_x;
+            player.y = next_y;
+            players_index.insert(i, &player.circle());
+        }
+    }
+
+    let mut alived_teams: Vec<usize> = players
+        .iter()
+        .filter(|p| p.status == PlayerStatus::InGame)
+        .map(|p| p.get_team())
+        .collect();
+    alived_teams.sort_unstable();
+    alived_teams.dedup();
+
+    if alived_teams.is_empty() {
+        *status = GameStatus::OverDraw;
+        events.push(GameEvent {
+            time,
+            event: Event::GameOver { winner_team: None },
+        });
+        return;
+    } else if alived_teams.len() == 1 {
+        for player in players.iter_mut() {
+            if player.status == PlayerStatus::InGame {
+                player.status = PlayerStatus::Win;
+            }
+        }
+        *status = GameStatus::Over(alived_teams[0]);
+        events.push(GameEvent {
+            time,
+            event: Event::GameOver {
+                winner_team: Some(alived_teams[0]),
+            },
+        });
+        return;
+    }
 
-                // Calculate next coordinates
+    // Players can be hit anywhere along their path, so the query area is extended by it
+    let max_displacement = players
+        .iter()
+        .zip(previous_positions.iter())
+        .map(|(p, (x, y))| ((p.x - x).powi(2) + (p.y - y).powi(2)).sqrt())
+        .fold(0.0, f64::max);
 
-                let mut next_x =
-                    player.x + (player.direction * PI / 180.0).sin() * player.speed * timedelta;
-                let mut next_y =
-                    player.y + (player.direction * PI / 180.0).cos() * player.speed * timedelta;
+    // Missiles are swept along their path during the step, so fast missiles can't tunnel
+    // through barriers and players. Every contact along the path is kept in time order.
 
-                // Borders collision detection and handling
+    let paths: Vec<(f64, f64)> = missiles
+        .iter()
+        .map(|m| {
+            let (vx, vy) = velocity(m.direction, m.speed);
+            (m.x + vx * timedelta, m.y + vy * timedelta)
+        })
+        .collect();
+    let mut timeline: Vec<(f64, Contact)> = Vec::new();
 
-                if next_x - player.r < 0.0 {
-                    next_x = player.r;
-                } else if next_x + player.r > map.width {
-                    next_x = map.width - player.r;
-                }
-                if next_y - player.r < 0.0 {
-                    next_y = player.r;
-                } else if next_y + player.r > map.height {
-                    next_y = map.height - player.r;
-                }
+    for (k, (m, &(next_x, next_y))) in missiles.iter().zip(paths.iter()).enumerate() {
+        let mut push = |t: f64, target: MissileHit| timeline.push((t, Contact::Hit(k, target)));
 
-                // Barriers collision detection
+        // Borders collision
 
-                for barrier in map.barriers.iter() {
-                    let distance =
-                        ((next_x - barrier.x).powi(2) + (next_y - barrier.y).powi(2)).sqrt();
-                    if distance < (player.r + barrier.r) {
-                        // Don't move player if detect collision
-                        next_x = player.x;
-                        next_y = player.y;
-                        break;
-                    }
-                }
+        if let Some(t) = segment_rect_exit(m.x, m.y, next_x, next_y, map.width, map.height) {
+            push(t, MissileHit::Border);
+        }
 
-                // Players collision detection
-                // todo
+        // Barriers collision
 
-                player.x = next_x;
-                player.y = next_y;
-                alived_players_count += 1;
-                alived_player = Some(Arc::clone(player_arc));
+        barriers_index.query_segment(m.x, m.y, next_x, next_y, 0.0, &mut candidates);
+        for &j in candidates.iter() {
+            if let Some(t) = map.barriers[j].segment_intersection(m.x, m.y, next_x, next_y) {
+                push(t, MissileHit::Barrier(j));
             }
+        }
 
-            if alived_players_count == 0 {
-                *status = GameStatus::OverDraw;
-                break;
-            } else if alived_players_count == 1 {
-                let player = alived_player.unwrap();
-                {
-                    player.lock().unwrap().status = PlayerStatus::Win;
+        // Players collision, swept in the player's frame of reference because players
+        // have moved during the step too
+
+        players_index.query_segment(m.x, m.y, next_x, next_y, max_displacement, &mut candidates);
+        for &i in candidates.iter() {
+            let p = &players[i];
+            if m.player_id == p.id
+                || p.status != PlayerStatus::InGame
+                || (!config.friendly_fire && m.team == p.get_team())
+            {
+                continue;
+            }
+            let (previous_x, previous_y) = previous_positions[i];
+            if let Some(t) = segment_circle_intersection(
+                m.x - previous_x,
+                m.y - previous_y,
+                next_x - p.x,
+                next_y - p.y,
+                0.0,
+                0.0,
+                p.r,
+            ) {
+                push(t, MissileHit::Player(i));
+            }
+        }
+
+        // Range and lifetime, pushed last so hits at the same moment still count
+
+        let length = m.speed * timedelta;
+        if let Some(range) = config.missile_range {
+            let remaining = range - m.traveled;
+            if remaining <= length {
+                push((remaining / length).max(0.0), MissileHit::Expired);
+            }
+        }
+        if let Some(ttl) = config.missile_ttl {
+            let remaining = ttl - m.age;
+            if remaining <= timedelta {
+                push((remaining / timedelta).max(0.0), MissileHit::Expired);
+            }
+        }
+    }
+
+    if config.missile_interception {
+        for (t, a, b) in find_interceptions(missiles, &paths, map, config.missile_radius) {
+            timeline.push((t, Contact::Interception(a, b)));
+        }
+    }
+
+    // Contacts are resolved in time order and the first one a missile reaches stops it, so the
+    // missile landing the final blow gets the kill. A missile whose target is destroyed earlier
+    // in the step flies on over the wreck to its next contact. Hits come before interceptions
+    // at the same moment and ties are broken by missile ids.
+    let rank = |contact: &Contact| match *contact {
+        Contact::Hit(k, _) => (0, missiles[k].id),
+        Contact::Interception(a, b) => (1, missiles[a].id.min(missiles[b].id)),
+    };
+    timeline.sort_by(|(ta, a), (tb, b)| ta.total_cmp(tb).then(rank(a).cmp(&rank(b))));
+
+    let mut stopped = vec![false; missiles.len()];
+    for (t, contact) in timeline {
+        let hit_time = time - (1.0 - t) * timedelta;
+        let hit_point = |k: usize| {
+            let (m, (next_x, next_y)) = (&missiles[k], paths[k]);
+            (m.x + (next_x - m.x) * t, m.y + (next_y - m.y) * t)
+        };
+        let mut push = |event: Event| {
+            events.push(GameEvent {
+                time: hit_time,
+                event,
+            })
+        };
+        match contact {
+            // Skipped if the missile is already stopped or flies over a wreck
+            Contact::Hit(k, target)
+                if stopped[k]
+                    || matches!(target, MissileHit::Player(i)
+                        if players[i].status != PlayerStatus::InGame) => {}
+            Contact::Hit(k, target) => {
+                stopped[k] = true;
+                let (m, (x, y)) = (&missiles[k], hit_point(k));
+                let missile_id = m.id;
+                match target {
+                    MissileHit::Player(i) => {
+                        let damage = players[i].health.damage(config.missile_damage);
+                        let killed = !players[i].health.is_alive();
+                        let player_id = players[i].id;
+                        push(Event::PlayerHit {
+                            missile_id,
+                            attacker_id: m.player_id,
+                            player_id,
+                            damage,
+                            x,
+                            y,
+                        });
+                        if killed {
+                            players[i].status = PlayerStatus::KilledBy(m.player_id);
+                            push(Event::PlayerKilled {
+                                player_id,
+                                killer_id: m.player_id,
+                                x: players[i].x,
+                                y: players[i].y,
+                            });
+                        }
+                        if let Some(attacker) = players.iter_mut().find(|p| p.id == m.player_id) {
+                            attacker.damage_dealt += damage;
+                            attacker.kills += killed as usize;
+                        }
+                    }
+                    MissileHit::Border => push(Event::MissileHitBorder { missile_id, x, y }),
+                    MissileHit::Barrier(barrier) => push(Event::MissileHitBarrier {
+                        missile_id,
+                        barrier,
+                        x,
+                        y,
+                    }),
+                    MissileHit::Expired => push(Event::MissileExpired { missile_id, x, y }),
                 }
-                *status = GameStatus::Over(player);
-                break;
             }
+            Contact::Interception(a, b) if !stopped[a] && !stopped[b] => {
+                stopped[a] = true;
+                stopped[b] = true;
+                for (k, other) in [(a, b), (b, a)] {
+                    let (x, y) = hit_point(k);
+                    push(Event::MissileIntercepted {
+                        missile_id: missiles[k].id,
+                        other_id: missiles[other].id,
+                        x,
+                        y,
+                    });
+                }
+            }
+            Contact::Interception(..) => {}
+        }
+    }
+
+    let mut stopped = stopped.into_iter().zip(paths);
+    missiles.retain_mut(|m| {
+        let (stopped, (next_x, next_y)) = stopped.next().unwrap();
+        if !stopped {
+            m.x = next_x;
+            m.y = next_y;
+            m.traveled += m.speed * timedelta;
+            m.age += timedelta;
+        }
+        !stopped
+    });
+}
+
+impl GameTrait for Game {
+    fn process(&mut self, time: f64) {
+        self.pending_time += time;
+        let Game {
+            ref map,
+            ref config,
+            ref barriers_index,
+            ref players,
+            ref missiles,
+            ref events,
+            ref mut status,
+            ref mut time,
+            ref mut pending_time,
+            ..
+        } = *self;
+        let arena = Arena {
+            map,
+            barriers_index,
+            config,
+        };
 
+        run_steps(config, time, pending_time, |timedelta, time| {
+            // Players are locked once per step in the registration order
+            let mut locked_players: Vec<MutexGuard<Player>> =
+                players.iter().map(|p| p.lock().unwrap()).collect();
             let mut locked_missiles = missiles.lock().unwrap();
+            let mut step_events = Vec::new();
+            step(
+                &arena,
+                &mut locked_players,
+                &mut locked_missiles,
+                status,
+                time,
+                timedelta,
+                &mut step_events,
+            );
+            drop(locked_missiles);
+            drop(locked_players);
 
-            for missile in locked_missiles.iter_mut() {
-                missile.x += (missile.direction * PI / 180.0).sin() * missile.speed * timedelta;
-                missile.y += (missile.direction * PI / 180.0).cos() * missile.speed * timedelta;
-            }
+            let mut log = events.lock().unwrap();
+            log.time = time;
+            log.events.extend(step_events);
+            *status == GameStatus::On
+        });
+    }
+}
 
-            // Borders collision
+/// Applies the collision response when the `i` player moving to `next` with `velocity` touches
+/// the `j` player and returns the velocity of the `i` player after the contact. The positions are
+/// not changed here, the moving player just stays where it was.
+fn resolve_players_collision<P: DerefMut<Target = Player>>(
+    players: &mut [P],
+    i: usize,
+    j: usize,
+    next: (f64, f64),
+    velocity: (f64, f64),
+    config: &GameConfig,
+) -> (f64, f64) {
+    let (next_x, next_y) = next;
+    let (vx, vy) = velocity;
+    let (other_x, other_y) = (players[j].x, players[j].y);
+
+    // Unit normal of the contact pointing from the other player to the moving one
+    let distance = ((next_x - other_x).powi(2) + (next_y - other_y).powi(2)).sqrt();
+    let (nx, ny) = if distance > 0.0 {
+        ((next_x - other_x) / distance, (next_y - other_y) / distance)
+    } else {
+        let (dx, dy) = self::velocity(players[i].direction, 1.0);
+        (-dx, -dy)
+    };
 
-            locked_missiles
-                .retain(|m| m.x >= 0.0 && m.y >= 0.0 && m.x <= map.width && m.y <= map.height);
+    // Allies don't ram each other without friendly fire
+    let allies = players[i].get_team() == players[j].get_team();
+    let response = match config.collision_response {
+        CollisionResponse::Ram if allies && !config.friendly_fire => CollisionResponse::Block,
+        response => response,
+    };
 
-            // Barriers collision
+    match response {
+        CollisionResponse::Block => (0.0, 0.0),
+        CollisionResponse::Bounce => {
+            let dot = vx * nx + vy * ny;
+            // Reflect only if the player moves toward the other one
+            if dot >= 0.0 {
+                return (0.0, 0.0);
+            }
+            let rx = vx - 2.0 * dot * nx;
+            let ry = vy - 2.0 * dot * ny;
+            // Arcade ships fly where they look, Newtonian ones keep looking forward
+            if config.flight_model == FlightModel::Arcade {
+                players[i].direction = rx.atan2(ry) * 180.0 / PI;
+            }
+            (rx, ry)
+        }
+        CollisionResponse::Ram => {
+            // Compare closing speeds along the contact normal, the faster one survives
+            let (other_vx, other_vy) = player_velocity(&players[j], config.flight_model);
+            let closing_speed = -(vx * nx + vy * ny);
+            let other_closing_speed = other_vx * nx + other_vy * ny;
+
+            let player_id = players[i].id;
+            let other_id = players[j].id;
+            if closing_speed >= other_closing_speed {
+                players[j].status = PlayerStatus::KilledBy(player_id);
+                players[i].kills += 1;
+            }
+            if other_closing_speed >= closing_speed {
+                players[i].status = PlayerStatus::KilledBy(other_id);
+                players[j].kills += 1;
+            }
+            (0.0, 0.0)
+        }
+    }
+}
 
-            locked_missiles.retain(|m| {
-                map.barriers
-                    .iter()
-                    .all(|b| ((m.x - b.x).powi(2) + (m.y - b.y).powi(2)).sqrt() >= b.r)
-            });
+/// What stops a missile during a step
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum MissileHit {
+    Border,
+    /// Index of the barrier
+    Barrier(usize),
+    /// Index of the player
+    Player(usize),
+    /// The missile runs out of range or lifetime
+    Expired,
+}
 
-            // Players collision
+/// Contact of a missile during a step, with indices of the missiles
+enum Contact {
+    Hit(usize, MissileHit),
+    /// Two missiles of different teams meet
+    Interception(usize, usize),
+}
 
-            locked_missiles.retain(|m| {
-                players.iter().map(|p| p.lock().unwrap()).all(|mut p| {
-                    let is_collision = m.player_id != p.id
-                        && ((m.x - p.x).powi(2) + (m.y - p.y).powi(2)).sqrt() < p.r
-                        && p.status == PlayerStatus::InGame;
-                    if is_collision {
-                        p.status = PlayerStatus::KilledBy(m.player_id);
-                    }
-                    !is_collision
-                })
-            });
+/// Finds missiles of different teams that meet during the step, as fractions of the step with
+/// the indices of both missiles
+fn find_interceptions(
+    missiles: &[Missile],
+    paths: &[(f64, f64)],
+    map: &Map,
+    radius: f64,
+) -> Vec<(f64, usize, usize)> {
+    // Every missile is indexed by the circle around its whole path during the step
+    let swept: Vec<Circle> = missiles
+        .iter()
+        .zip(paths.iter())
+        .map(|(m, &(next_x, next_y))| Circle {
+            x: (m.x + next_x) / 2.0,
+            y: (m.y + next_y) / 2.0,
+            r: ((next_x - m.x).powi(2) + (next_y - m.y).powi(2)).sqrt() / 2.0 + radius,
+        })
+        .collect();
+    let missiles_index = SpatialGrid::from_circles(map.width, map.height, &swept);
+
+    let mut candidates = Vec::new();
+    let mut contacts: Vec<(f64, usize, usize)> = Vec::new();
+    for (a, circle) in swept.iter().enumerate() {
+        missiles_index.query_circle(circle.x, circle.y, circle.r, &mut candidates);
+        for &b in candidates.iter().filter(|&&b| b > a) {
+            let (ma, mb) = (&missiles[a], &missiles[b]);
+            if ma.team == mb.team {
+                continue;
+            }
+            // Swept in the frame of reference of the second missile
+            if let Some(t) = segment_circle_intersection(
+                ma.x - mb.x,
+                ma.y - mb.y,
+                paths[a].0 - paths[b].0,
+                paths[a].1 - paths[b].1,
+                0.0,
+                0.0,
+                2.0 * radius,
+            ) {
+                contacts.push((t, a, b));
+            }
         }
     }
+    contacts
+}
+
+/// Velocity the player is going to move with in the current step
+pub(crate) fn player_velocity(player: &Player, flight_model: FlightModel) -> (f64, f64) {
+    match flight_model {
+        FlightModel::Arcade => velocity(player.direction, player.speed),
+        FlightModel::Newtonian(_) => (player.vx, player.vy),
+    }
+}
+
+fn velocity(direction: f64, speed: f64) -> (f64, f64) {
+    (
+        (direction * PI / 180.0).sin() * speed,
+        (direction * PI / 180.0).cos() * speed,
+    )
 }
 
 impl GameTrait for Arc<Mutex<Game>> {
@@ -168,9 +838,24 @@ impl GameTrait for Arc<Mutex<Game>> {
 
 impl RegisterPlayer for Mutex<Game> {
     fn register_player(self: &Arc<Self>, player: &Arc<Mutex<Player>>) {
-        player.lock().unwrap().mount_game(self);
+        let mut locked_player = player.lock().unwrap();
+        let mut game = self.lock().unwrap();
+        locked_player.mount_game(self, &mut game);
+        game.players.push(Arc::clone(player));
+    }
+
+    fn spawn_player(self: &Arc<Self>, player: &Arc<Mutex<Player>>) -> Result<(), SpawnError> {
+        let mut locked_player = player.lock().unwrap();
         let mut game = self.lock().unwrap();
+        // The game stays locked until the player is in, so no one else takes the place
+        let spawn_point = game.spawn_point(locked_player.r)?;
+        locked_player.x = spawn_point.x;
+        locked_player.y = spawn_point.y;
+        locked_player.direction = spawn_point.direction;
+        locked_player.random_direction = false;
+        locked_player.mount_game(self, &mut game);
         game.players.push(Arc::clone(player));
+        Ok(())
     }
 }
 
@@ -179,11 +864,18 @@ mod tests {
     use std::sync::{Arc, Mutex};
 
     use crate::{
+        config::GameConfig,
+        events::Event,
         map::{Barrier, Map},
-        player::{Player, PlayerTrait},
+        player::{Player, PlayerStatus, PlayerTrait},
+        shape::Shape,
+        spawn::{SpawnConfig, SpawnError},
     };
 
-    use super::{Game, GameTrait, RegisterPlayer};
+    use super::{
+        CollisionResponse, FlightModel, Game, GameStatus, GameTrait, NewtonianConfig,
+        RegisterPlayer, Tiebreak,
+    };
 
     const SEED: u64 = 12345;
     const MISSILE_SPEED: f64 = 1.0;
@@ -192,6 +884,8 @@ mod tests {
         (value * 100000.0).round() / 100000.0
     }
 
+    type TwoPlayersSetup = (Arc<Mutex<Player>>, Arc<Mutex<Player>>, Arc<Mutex<Game>>);
+
     fn get_stub_player() -> Arc<Mutex<Player>> {
         Player::new_with_direction(99.0, 99.0, 0.5, 1.0, 60.0, 7, -180.0, MISSILE_SPEED)
     }
@@ -200,7 +894,7 @@ mod tests {
     fn test_movement() {
         let mut p = Player::new_with_direction(1.0, 1.0, 1.0, 1.0, 60.0, 7, 0.0, MISSILE_SPEED);
         let stub_p = get_stub_player();
-        let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED));
+        let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED), GameConfig::default());
         game.register_player(&p);
         game.register_player(&stub_p);
         p.set_speed(0.5);
@@ -217,7 +911,7 @@ mod tests {
     fn test_borders_collision() {
         let mut p = Player::new_with_direction(1.0, 1.0, 0.5, 1.0, 60.0, 7, -180.0, MISSILE_SPEED);
         let stub_p = get_stub_player();
-        let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED));
+        let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED), GameConfig::default());
         game.register_player(&p);
         game.register_player(&stub_p);
         p.set_speed(1.0);
@@ -235,17 +929,9 @@ mod tests {
         let mut p = Player::new_with_direction(1.0, 1.0, 1.0, 1.0, 60.0, 7, 0.0, MISSILE_SPEED);
         let stub_p = get_stub_player();
         let mut map = Map::new(100.0, 100.0, 0, 0.0, SEED);
-        map.barriers.push(Barrier {
-            x: 1.0,
-            y: 3.0,
-            r: 1.0,
-        });
-        map.barriers.push(Barrier {
-            x: 3.0,
-            y: 1.0,
-            r: 1.0,
-        });
-        let mut game = Game::new(map);
+        map.barriers.push(Barrier::circle(1.0, 3.0, 1.0));
+        map.barriers.push(Barrier::circle(3.0, 1.0, 1.0));
+        let mut game = Game::new(map, GameConfig::default());
         game.register_player(&p);
         game.register_player(&stub_p);
         p.set_speed(1.0);
@@ -258,6 +944,34 @@ mod tests {
         assert_eq!(round_position(p.get_y()), 1.0);
     }
 
+    #[test]
+    fn test_shaped_barriers_collision() {
+        let mut p = Player::new_with_direction(10.0, 10.0, 1.0, 1.0, 60.0, 7, 0.0, MISSILE_SPEED);
+        let stub_p = get_stub_player();
+        let mut map = Map::new(100.0, 100.0, 0, 0.0, SEED);
+        // A wall across the way spanning from y = 14 to y = 16
+        map.barriers.push(Barrier {
+            x: 10.0,
+            y: 15.0,
+            shape: Shape::Rect {
+                width: 20.0,
+                height: 2.0,
+                angle: 0.0,
+            },
+        });
+        let mut game = Game::new(map, GameConfig::default());
+        game.register_player(&p);
+        game.register_player(&stub_p);
+        p.fire().unwrap();
+        p.set_speed(1.0);
+
+        game.process(6.0);
+
+        assert!(p.get_y() > 12.0 && p.get_y() <= 13.0);
+        let locked_game = game.lock().unwrap();
+        assert_eq!(locked_game.missiles.lock().unwrap().len(), 0);
+    }
+
     #[test]
     fn test_missiles_movement() {
         const START_X: f64 = 1.0;
@@ -266,7 +980,7 @@ mod tests {
         let mut p =
             Player::new_with_direction(START_X, START_Y, 1.0, 1.0, 60.0, 7, 0.0, MISSILE_SPEED);
         let stub_p = get_stub_player();
-        let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED));
+        let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED), GameConfig::default());
         game.register_player(&p);
         game.register_player(&stub_p);
 
@@ -277,9 +991,9 @@ mod tests {
         }
 
         // Launch two missiles in different directions
-        p.fire();
+        p.fire().unwrap();
         p.rotate(90.0);
-        p.fire();
+        p.fire().unwrap();
         game.process(1.0);
 
         {
@@ -321,18 +1035,21 @@ mod tests {
         let mut p =
             Player::new_with_direction(START_X, START_Y, 1.0, 1.0, 60.0, 7, 0.0, MISSILE_SPEED);
         let stub_p = get_stub_player();
-        let mut game = Game::new(Map::new(MAP_SIZE, MAP_SIZE, 0, 0.0, SEED));
+        let mut game = Game::new(
+            Map::new(MAP_SIZE, MAP_SIZE, 0, 0.0, SEED),
+            GameConfig::default(),
+        );
         game.register_player(&p);
         game.register_player(&stub_p);
 
         // Launch missiles in different directions to check collision for each border
-        p.fire();
+        p.fire().unwrap();
         p.rotate(90.0);
-        p.fire();
+        p.fire().unwrap();
         p.rotate(90.0);
-        p.fire();
+        p.fire().unwrap();
         p.rotate(90.0);
-        p.fire();
+        p.fire().unwrap();
 
         // Move until the last frame before collision
         game.process(49.0);
@@ -384,20 +1101,17 @@ mod tests {
         const TARGET_X: f64 = 10.0;
         const TARGET_Y: f64 = 20.0;
 
-        let p = Player::new_with_direction(START_X, START_Y, 1.0, 1.0, 90.0, 7, 0.0, MISSILE_SPEED);
+        let mut p =
+            Player::new_with_direction(START_X, START_Y, 1.0, 1.0, 90.0, 7, 0.0, MISSILE_SPEED);
         let stub_p = get_stub_player();
         let mut map = Map::new(100.0, 100.0, 0, 0.0, SEED);
-        map.barriers.push(Barrier {
-            x: TARGET_X,
-            y: TARGET_Y,
-            r: 1.0,
-        });
-        let mut game = Game::new(map);
+        map.barriers.push(Barrier::circle(TARGET_X, TARGET_Y, 1.0));
+        let mut game = Game::new(map, GameConfig::default());
         game.register_player(&p);
         game.register_player(&stub_p);
 
         // Launch a missile into a barrier
-        p.fire();
+        p.fire().unwrap();
 
         {
             let locked_game = game.lock().unwrap();
@@ -425,4 +1139,719 @@ mod tests {
             assert_eq!(missiles.len(), 0);
         }
     }
+
+    #[test]
+    fn test_time_is_not_dropped() {
+        let mut p = Player::new_with_direction(1.0, 1.0, 0.5, 1.0, 60.0, 7, 0.0, MISSILE_SPEED);
+        let stub_p = get_stub_player();
+        let config = GameConfig {
+            time_step: 0.3,
+            ..Default::default()
+        };
+        let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED), config);
+        game.register_player(&p);
+        game.register_player(&stub_p);
+        p.set_speed(1.0);
+
+        game.process(0.12345);
+        game.process(1.0);
+
+        assert!((p.get_y() - 2.12345).abs() < 1e-12);
+        assert!((game.lock().unwrap().time - 1.12345).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_max_substeps() {
+        let mut p = Player::new_with_direction(1.0, 1.0, 0.5, 1.0, 60.0, 7, 0.0, MISSILE_SPEED);
+        let stub_p = get_stub_player();
+        let config = GameConfig {
+            time_step: 0.1,
+            max_substeps: 5,
+            ..Default::default()
+        };
+        let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED), config);
+        game.register_player(&p);
+        game.register_player(&stub_p);
+        p.set_speed(1.0);
+
+        // Only 5 steps are simulated, the rest of the time is carried over
+        game.process(1.0);
+        assert_eq!(round_position(p.get_y()), 1.5);
+
+        game.process(0.0);
+        assert_eq!(round_position(p.get_y()), 2.0);
+        assert_eq!(round_position(game.lock().unwrap().time), 1.0);
+    }
+
+    fn play_seeded_game() -> Vec<(usize, f64, f64, f64)> {
+        let config = GameConfig {
+            seed: Some(SEED),
+            ..Default::default()
+        };
+        let mut game = Game::new(Map::new(960.0, 540.0, 30, 40.0, SEED), config);
+        let mut players = Vec::new();
+        for _ in 0..3 {
+            let (x, y) = game.lock().unwrap().get_free_point(10.0).unwrap();
+            let p = Player::new(x, y, 10.0, 100.0, 60.0, 7, 300.0);
+            game.register_player(&p);
+            players.push(p);
+        }
+
+        for (i, p) in players.iter_mut().enumerate() {
+            p.set_speed(50.0 * (i + 1) as f64);
+            p.rotate(30.0);
+            p.fire().unwrap();
+        }
+        game.process(1.5);
+
+        let mut state: Vec<(usize, f64, f64, f64)> = players
+            .iter()
+            .map(|p| (p.get_id(), p.get_x(), p.get_y(), p.get_direction()))
+            .collect();
+        let locked_game = game.lock().unwrap();
+        for m in locked_game.missiles.lock().unwrap().iter() {
+            state.push((m.id, m.x, m.y, m.direction));
+        }
+        state
+    }
+
+    #[test]
+    fn test_seeded_game_is_deterministic() {
+        let state1 = play_seeded_game();
+        let state2 = play_seeded_game();
+
+        // Ids, positions and directions must be bit-identical
+        assert_eq!(state1.len(), state2.len());
+        for (s1, s2) in state1.iter().zip(state2.iter()) {
+            assert_eq!(s1.0, s2.0);
+            assert_eq!(s1.1.to_bits(), s2.1.to_bits());
+            assert_eq!(s1.2.to_bits(), s2.2.to_bits());
+            assert_eq!(s1.3.to_bits(), s2.3.to_bits());
+        }
+    }
+
+    #[test]
+    fn test_ids_are_allocated_per_game() {
+        for _ in 0..2 {
+            let game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED), GameConfig::default());
+            let p = get_stub_player();
+            game.register_player(&p);
+            assert_eq!(p.get_id(), 1);
+        }
+    }
+
+    #[test]
+    fn test_fast_missile_hits_player() {
+        const FAST_MISSILE_SPEED: f64 = 2880.0;
+
+        // The missile moves much further than the player size during one step
+        let mut p =
+            Player::new_with_direction(10.0, 10.0, 1.0, 1.0, 60.0, 7, 0.0, FAST_MISSILE_SPEED);
+        let target = Player::new_with_direction(10.0, 60.0, 1.0, 1.0, 60.0, 7, 0.0, MISSILE_SPEED);
+        let mut game = Game::new(
+            Map::new(1000.0, 1000.0, 0, 0.0, SEED),
+            GameConfig::default(),
+        );
+        game.register_player(&p);
+        game.register_player(&target);
+
+        p.fire().unwrap();
+        game.process(0.1);
+
+        assert_eq!(
+            target.lock().unwrap().status,
+            PlayerStatus::KilledBy(p.get_id())
+        );
+        let locked_game = game.lock().unwrap();
+        assert_eq!(locked_game.missiles.lock().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_fast_missile_hits_barrier() {
+        const FAST_MISSILE_SPEED: f64 = 2880.0;
+
+        // The barrier shields the target, so the missile must stop at the barrier
+        let mut p =
+            Player::new_with_direction(10.0, 10.0, 1.0, 1.0, 60.0, 7, 0.0, FAST_MISSILE_SPEED);
+        let target = Player::new_with_direction(10.0, 90.0, 1.0, 1.0, 60.0, 7, 0.0, MISSILE_SPEED);
+        let mut map = Map::new(1000.0, 1000.0, 0, 0.0, SEED);
+        map.barriers.push(Barrier::circle(10.0, 50.0, 5.0));
+        let mut game = Game::new(map, GameConfig::default());
+        game.register_player(&p);
+        game.register_player(&target);
+
+        p.fire().unwrap();
+        game.process(0.1);
+
+        assert_eq!(target.lock().unwrap().status, PlayerStatus::InGame);
+        let locked_game = game.lock().unwrap();
+        assert_eq!(locked_game.missiles.lock().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_fast_missile_hits_nearest_player() {
+        const FAST_MISSILE_SPEED: f64 = 2880.0;
+
+        let mut p =
+            Player::new_with_direction(10.0, 10.0, 1.0, 1.0, 60.0, 7, 0.0, FAST_MISSILE_SPEED);
+        let far_target =
+            Player::new_with_direction(10.0, 200.0, 1.0, 1.0, 60.0, 7, 0.0, MISSILE_SPEED);
+        let near_target =
+            Player::new_with_direction(10.0, 100.0, 1.0, 1.0, 60.0, 7, 0.0, MISSILE_SPEED);
+        let mut game = Game::new(
+            Map::new(1000.0, 1000.0, 0, 0.0, SEED),
+            GameConfig::default(),
+        );
+        game.register_player(&p);
+        game.register_player(&far_target);
+        game.register_player(&near_target);
+
+        p.fire().unwrap();
+        game.process(0.1);
+
+        assert_eq!(far_target.lock().unwrap().status, PlayerStatus::InGame);
+        assert_eq!(
+            near_target.lock().unwrap().status,
+            PlayerStatus::KilledBy(p.get_id())
+        );
+    }
+
+    #[test]
+    fn test_missile_range_and_ttl() {
+        for (range, ttl) in [(Some(5.0), None), (None, Some(5.0))] {
+            let mut p = Player::new_with_direction(50.0, 10.0, 1.0, 1.0, 60.0, 7, 0.0, 1.0);
+            let stub_p = get_stub_player();
+            let config = GameConfig {
+                missile_range: range,
+                missile_ttl: ttl,
+                ..Default::default()
+            };
+            let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED), config);
+            game.register_player(&p);
+            game.register_player(&stub_p);
+
+            p.fire().unwrap();
+            game.process(4.0);
+            {
+                let locked_game = game.lock().unwrap();
+                let missiles = locked_game.missiles.lock().unwrap();
+                assert_eq!(missiles.len(), 1);
+                assert!((missiles[0].traveled - 4.0).abs() < 1e-9);
+                assert!((missiles[0].age - 4.0).abs() < 1e-9);
+            }
+
+            game.process(1.1);
+            let locked_game = game.lock().unwrap();
+            assert_eq!(locked_game.missiles.lock().unwrap().len(), 0);
+        }
+    }
+
+    #[test]
+    fn test_damage_and_final_blow() {
+        let mut a = Player::new_with_direction(50.0, 10.0, 1.0, 1.0, 60.0, 7, 0.0, 2880.0);
+        let mut b = Player::new_with_direction(10.0, 50.0, 1.0, 1.0, 60.0, 7, 90.0, 2880.0);
+        let target = Player::new_with_direction(50.0, 50.0, 1.0, 1.0, 60.0, 7, 0.0, 2880.0);
+        let config = GameConfig {
+            player_hp: 3.0,
+            missile_damage: 1.0,
+            ..Default::default()
+        };
+        let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED), config);
+        game.register_player(&a);
+        game.register_player(&b);
+        game.register_player(&target);
+
+        a.fire().unwrap();
+        a.fire().unwrap();
+        game.process(0.1);
+        assert_eq!(target.lock().unwrap().status, PlayerStatus::InGame);
+        assert_eq!(target.lock().unwrap().health.hp, 1.0);
+
+        b.fire().unwrap();
+        game.process(0.1);
+        assert_eq!(
+            target.lock().unwrap().status,
+            PlayerStatus::KilledBy(b.get_id())
+        );
+        assert_eq!(a.lock().unwrap().damage_dealt, 2.0);
+        assert_eq!(a.lock().unwrap().kills, 0);
+        assert_eq!(b.lock().unwrap().damage_dealt, 1.0);
+        assert_eq!(b.lock().unwrap().kills, 1);
+    }
+
+    #[test]
+    fn test_earliest_hit_gets_the_kill() {
+        let mut a = Player::new_with_direction(50.0, 10.0, 1.0, 1.0, 60.0, 7, 0.0, 1000.0);
+        let mut b = Player::new_with_direction(30.0, 50.0, 1.0, 1.0, 60.0, 7, 90.0, 1000.0);
+        let target = Player::new_with_direction(50.0, 50.0, 1.0, 1.0, 60.0, 7, 0.0, 1000.0);
+        let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED), GameConfig::default());
+        game.register_player(&a);
+        game.register_player(&b);
+        game.register_player(&target);
+
+        // The missile of `a` comes first in the vector, but the one of `b` is closer
+        a.fire().unwrap();
+        b.fire().unwrap();
+        game.process(0.1);
+
+        assert_eq!(
+            target.lock().unwrap().status,
+            PlayerStatus::KilledBy(b.get_id())
+        );
+        assert_eq!(b.lock().unwrap().kills, 1);
+        assert_eq!(a.lock().unwrap().kills, 0);
+        assert_eq!(a.lock().unwrap().damage_dealt, 0.0);
+
+        // The missile of `a` flies on over the wreck and leaves the map
+        let locked_game = game.lock().unwrap();
+        assert!(locked_game.missiles.lock().unwrap().is_empty());
+        let events = locked_game.drain_events();
+        let hits = events
+            .iter()
+            .filter(|e| matches!(e.event, Event::PlayerHit { .. }))
+            .count();
+        assert_eq!(hits, 1);
+        assert!(events
+            .iter()
+            .any(|e| matches!(e.event, Event::MissileHitBorder { .. })));
+    }
+
+    #[test]
+    fn test_missile_flies_over_wreck_into_barrier() {
+        let mut a = Player::new_with_direction(50.0, 10.0, 1.0, 1.0, 60.0, 7, 0.0, 1000.0);
+        let mut b = Player::new_with_direction(30.0, 50.0, 1.0, 1.0, 60.0, 7, 90.0, 1000.0);
+        let target = Player::new_with_direction(50.0, 50.0, 1.0, 1.0, 60.0, 7, 0.0, 1000.0);
+        let mut map = Map::new(100.0, 100.0, 0, 0.0, SEED);
+        map.barriers.push(Barrier::circle(50.0, 70.0, 2.0));
+        let mut game = Game::new(map, GameConfig::default());
+        game.register_player(&a);
+        game.register_player(&b);
+        game.register_player(&target);
+
+        // The missile of `b` destroys the target, the one of `a` goes on to the barrier behind it
+        a.fire().unwrap();
+        b.fire().unwrap();
+        game.process(0.1);
+
+        assert_eq!(
+            target.lock().unwrap().status,
+            PlayerStatus::KilledBy(b.get_id())
+        );
+        let locked_game = game.lock().unwrap();
+        assert!(locked_game.missiles.lock().unwrap().is_empty());
+        let barrier_hit = locked_game
+            .drain_events()
+            .into_iter()
+            .find_map(|e| match e.event {
+                Event::MissileHitBarrier { barrier, y, .. } => Some((barrier, y)),
+                _ => None,
+            });
+        let (barrier, y) = barrier_hit.unwrap();
+        assert_eq!(barrier, 0);
+        assert_eq!(round_position(y), 68.0);
+    }
+
+    fn get_squads() -> Vec<Arc<Mutex<Player>>> {
+        let mut players = vec![
+            Player::new_with_direction(50.0, 10.0, 1.0, 1.0, 60.0, 7, 0.0, 2880.0),
+            Player::new_with_direction(50.0, 50.0, 1.0, 1.0, 60.0, 7, 0.0, 2880.0),
+            Player::new_with_direction(50.0, 90.0, 1.0, 1.0, 60.0, 7, 0.0, 2880.0),
+        ];
+        players[0].set_team(1);
+        players[1].set_team(1);
+        players[2].set_team(2);
+        players
+    }
+
+    #[test]
+    fn test_team_wins() {
+        let mut players = get_squads();
+        let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED), GameConfig::default());
+        for p in players.iter() {
+            game.register_player(p);
+        }
+
+        // The missile flies through the ally and hits the enemy
+        players[0].fire().unwrap();
+        game.process(0.2);
+
+        assert!(matches!(game.lock().unwrap().status, GameStatus::Over(1)));
+        assert_eq!(players[0].lock().unwrap().status, PlayerStatus::Win);
+        assert_eq!(players[1].lock().unwrap().status, PlayerStatus::Win);
+        assert_eq!(
+            players[2].lock().unwrap().status,
+            PlayerStatus::KilledBy(players[0].get_id())
+        );
+    }
+
+    #[test]
+    fn test_friendly_fire() {
+        let mut players = get_squads();
+        let config = GameConfig {
+            friendly_fire: true,
+            ..Default::default()
+        };
+        let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED), config);
+        for p in players.iter() {
+            game.register_player(p);
+        }
+
+        players[0].fire().unwrap();
+        game.process(0.2);
+
+        assert_eq!(
+            players[1].lock().unwrap().status,
+            PlayerStatus::KilledBy(players[0].get_id())
+        );
+        assert_eq!(players[2].lock().unwrap().status, PlayerStatus::InGame);
+        assert!(matches!(game.lock().unwrap().status, GameStatus::On));
+    }
+
+    #[test]
+    fn test_time_limit_draw() {
+        let p = Player::new_with_direction(10.0, 10.0, 1.0, 1.0, 60.0, 7, 0.0, MISSILE_SPEED);
+        let stub_p = get_stub_player();
+        let config = GameConfig {
+            time_limit: Some(1.05),
+            ..Default::default()
+        };
+        let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED), config);
+        game.register_player(&p);
+        game.register_player(&stub_p);
+
+        game.process(1.0);
+        assert!(matches!(game.lock().unwrap().status, GameStatus::On));
+
+        game.process(10.0);
+        assert!(matches!(game.lock().unwrap().status, GameStatus::OverDraw));
+        assert!((game.lock().unwrap().time - 1.05).abs() < 1e-9);
+        assert_eq!(p.lock().unwrap().status, PlayerStatus::Draw);
+        assert_eq!(stub_p.lock().unwrap().status, PlayerStatus::Draw);
+    }
+
+    #[test]
+    fn test_time_limit_tiebreak() {
+        for tiebreak in [Tiebreak::Kills, Tiebreak::Damage] {
+            // The first player shoots down the third one and damages the second one
+            let mut players = [
+                Player::new_with_direction(50.0, 10.0, 1.0, 1.0, 60.0, 7, 0.0, 2880.0),
+                Player::new_with_direction(90.0, 10.0, 1.0, 1.0, 60.0, 7, 0.0, 2880.0),
+                Player::new_with_direction(50.0, 90.0, 1.0, 1.0, 60.0, 7, 0.0, 2880.0),
+            ];
+            let config = GameConfig {
+                time_limit: Some(1.0),
+                tiebreak,
+                ..Default::default()
+            };
+            let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED), config);
+            for p in players.iter() {
+                game.register_player(p);
+            }
+
+            players[0].fire().unwrap();
+            game.process(0.5);
+            players[0].rotate(90.0);
+            players[1].lock().unwrap().health.hp = 2.0;
+            players[0].fire().unwrap();
+            game.process(1.0);
+
+            let winner = players[0].get_team();
+            assert!(matches!(game.lock().unwrap().status, GameStatus::Over(t) if t == winner));
+            assert_eq!(players[0].lock().unwrap().status, PlayerStatus::Win);
+            assert_eq!(players[1].lock().unwrap().status, PlayerStatus::Lose);
+        }
+    }
+
+    #[test]
+    fn test_events() {
+        let mut p = Player::new_with_direction(50.0, 10.0, 1.0, 1.0, 60.0, 7, 0.0, 2880.0);
+        let target = Player::new_with_direction(50.0, 60.0, 1.0, 1.0, 60.0, 7, 0.0, 2880.0);
+        let mut game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED), GameConfig::default());
+        game.register_player(&p);
+        game.register_player(&target);
+        let (p_id, target_id) = (p.get_id(), target.get_id());
+
+        p.fire().unwrap();
+        game.process(0.2);
+
+        let events = game.lock().unwrap().drain_events();
+        let kinds: Vec<&Event> = events.iter().map(|e| &e.event).collect();
+        assert!(matches!(kinds[..], [
+            Event::ShotFired { player_id, .. },
+            Event::PlayerHit { attacker_id, player_id: hit_id, .. },
+            Event::PlayerKilled { player_id: killed_id, killer_id, .. },
+            Event::GameOver { winner_team: Some(winner_team) },
+        ] if *player_id == p_id
+            && *attacker_id == p_id
+            && *hit_id == target_id
+            && *killed_id == target_id
+            && *killer_id == p_id
+            && *winner_team == p_id));
+
+        // The missile hits the target at the time it flies 49 units
+        assert_eq!(events[0].time, 0.0);
+        assert!((events[1].time - 49.0 / 2880.0).abs() < 1e-9);
+        assert_eq!(events[3].time, 0.2);
+
+        assert!(game.lock().unwrap().drain_events().is_empty());
+    }
+
+    fn get_duelists(interception: bool) -> TwoPlayersSetup {
+        let p = Player::new_with_direction(50.0, 10.0, 1.0, 1.0, 60.0, 7, 0.0, 2880.0);
+        let other = Player::new_with_direction(50.0, 990.0, 1.0, 1.0, 60.0, 7, 180.0, 2880.0);
+        let config = GameConfig {
+            missile_interception: interception,
+            ..Default::default()
+        };
+        let game = Game::new(Map::new(100.0, 1000.0, 0, 0.0, SEED), config);
+        game.register_player(&p);
+        game.register_player(&other);
+        (p, other, game)
+    }
+
+    #[test]
+    fn test_missiles_interception() {
+        // Head-on missiles pass each other within one step
+        let (mut p, mut other, mut game) = get_duelists(true);
+        p.fire().unwrap();
+        other.fire().unwrap();
+        game.process(0.2);
+        {
+            let locked_game = game.lock().unwrap();
+            assert_eq!(locked_game.missiles.lock().unwrap().len(), 0);
+        }
+        game.process(1.0);
+        assert_eq!(p.lock().unwrap().status, PlayerStatus::InGame);
+        assert_eq!(other.lock().unwrap().status, PlayerStatus::InGame);
+
+        // Without interception they fly through each other
+        let (mut p, mut other, mut game) = get_duelists(false);
+        p.fire().unwrap();
+        other.fire().unwrap();
+        game.process(0.2);
+        let locked_game = game.lock().unwrap();
+        assert_eq!(locked_game.missiles.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_own_missiles_are_not_intercepted() {
+        let (mut p, _, mut game) = get_duelists(true);
+        p.fire().unwrap();
+        p.rotate(1.0);
+        p.fire().unwrap();
+        game.process(0.1);
+        let locked_game = game.lock().unwrap();
+        assert_eq!(locked_game.missiles.lock().unwrap().len(), 2);
+    }
+
+    fn get_colliding_players(
+        response: CollisionResponse,
+        other_y: f64,
+        other_speed: f64,
+    ) -> TwoPlayersSetup {
+        let mut p = Player::new_with_direction(10.0, 50.0, 5.0, 10.0, 60.0, 7, 90.0, MISSILE_SPEED);
+        let mut other =
+            Player::new_with_direction(30.0, other_y, 5.0, 10.0, 60.0, 7, -90.0, MISSILE_SPEED);
+        let game = Game::new(
+            Map::new(100.0, 100.0, 0, 0.0, SEED),
+            GameConfig {
+                collision_response: response,
+                ..Default::default()
+            },
+        );
+        game.register_player(&p);
+        game.register_player(&other);
+        p.set_speed(10.0);
+        other.set_speed(other_speed);
+        (p, other, game)
+    }
+
+    #[test]
+    fn test_players_head_on_collision_block() {
+        let (p, other, mut game) = get_colliding_players(CollisionResponse::Block, 50.0, 10.0);
+
+        game.process(1.0);
+
+        // Players meet in the middle and stay there
+        assert_eq!(round_position(p.get_x()), 15.0);
+        assert_eq!(round_position(other.get_x()), 25.0);
+        assert_eq!(p.lock().unwrap().status, PlayerStatus::InGame);
+        assert_eq!(other.lock().unwrap().status, PlayerStatus::InGame);
+    }
+
+    #[test]
+    fn test_players_glancing_collision_block() {
+        let (p, other, mut game) = get_colliding_players(CollisionResponse::Block, 58.0, 0.0);
+
+        game.process(2.0);
+
+        // The moving player stops at the last position that doesn't overlap the other one
+        assert_eq!(round_position(p.get_x()), 24.0);
+        assert_eq!(round_position(p.get_y()), 50.0);
+        assert_eq!(round_position(other.get_x()), 30.0);
+        assert_eq!(round_position(other.get_y()), 58.0);
+    }
+
+    #[test]
+    fn test_players_head_on_collision_bounce() {
+        let (p, other, mut game) = get_colliding_players(CollisionResponse::Bounce, 50.0, 10.0);
+
+        game.process(1.0);
+
+        // Both players turn back and move apart
+        assert_eq!(round_position(p.get_direction()), -90.0);
+        assert_eq!(round_position(other.get_direction()), 90.0);
+        assert_eq!(round_position(p.get_x()), 11.0);
+        assert_eq!(round_position(other.get_x()), 29.0);
+    }
+
+    #[test]
+    fn test_players_glancing_collision_bounce() {
+        let (p, other, mut game) = get_colliding_players(CollisionResponse::Bounce, 58.0, 0.0);
+
+        game.process(2.0);
+
+        // The moving player is deflected away from the other one and keeps moving forward
+        let direction = p.get_direction();
+        assert!(direction > 90.0 && direction < 180.0);
+        assert!(p.get_x() > 24.0);
+        assert!(p.get_y() < 50.0);
+        assert!(
+            ((p.get_x() - other.get_x()).powi(2) + (p.get_y() - other.get_y()).powi(2)).sqrt()
+                >= 10.0
+        );
+    }
+
+    #[test]
+    fn test_players_head_on_collision_ram() {
+        let (p, other, mut game) = get_colliding_players(CollisionResponse::Ram, 50.0, 10.0);
+
+        game.process(1.0);
+
+        // Equal closing speeds destroy both players
+        let p_id = p.get_id();
+        let other_id = other.get_id();
+        assert_eq!(p.lock().unwrap().status, PlayerStatus::KilledBy(other_id));
+        assert_eq!(other.lock().unwrap().status, PlayerStatus::KilledBy(p_id));
+        assert!(matches!(game.lock().unwrap().status, GameStatus::OverDraw));
+    }
+
+    #[test]
+    fn test_players_glancing_collision_ram() {
+        let (p, other, mut game) = get_colliding_players(CollisionResponse::Ram, 58.0, 0.0);
+
+        game.process(2.0);
+
+        // The moving player destroys the standing one
+        assert_eq!(
+            other.lock().unwrap().status,
+            PlayerStatus::KilledBy(p.get_id())
+        );
+        assert_eq!(p.lock().unwrap().status, PlayerStatus::Win);
+        assert!(matches!(game.lock().unwrap().status, GameStatus::Over(_)));
+    }
+
+    fn get_newtonian_player(newtonian: NewtonianConfig) -> (Arc<Mutex<Player>>, Arc<Mutex<Game>>) {
+        let p = Player::new_with_direction(50.0, 10.0, 1.0, 20.0, 60.0, 7, 0.0, MISSILE_SPEED);
+        let stub_p = get_stub_player();
+        let config = GameConfig {
+            flight_model: FlightModel::Newtonian(newtonian),
+            ..Default::default()
+        };
+        let game = Game::new(Map::new(100.0, 100.0, 0, 0.0, SEED), config);
+        game.register_player(&p);
+        game.register_player(&stub_p);
+        (p, game)
+    }
+
+    #[test]
+    fn test_newtonian_acceleration() {
+        let (mut p, mut game) = get_newtonian_player(NewtonianConfig {
+            acceleration: 10.0,
+            max_angular_velocity: 90.0,
+            drag: 0.0,
+        });
+
+        // Speed commands are ignored, the ship only accelerates by thrust
+        p.set_speed(20.0);
+        game.process(1.0);
+        assert_eq!(p.get_y(), 10.0);
+
+        p.set_thrust(1.0);
+        game.process(1.0);
+        assert!((p.get_speed() - 10.0).abs() < 1e-9);
+        assert!((p.get_y() - 15.5).abs() < 1e-9);
+
+        // The ship keeps flying without thrust
+        p.set_thrust(0.0);
+        game.process(1.0);
+        assert!((p.get_speed() - 10.0).abs() < 1e-9);
+        assert!((p.get_y() - 25.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_newtonian_turn_rate() {
+        let (mut p, mut game) = get_newtonian_player(NewtonianConfig {
+            acceleration: 10.0,
+            max_angular_velocity: 90.0,
+            drag: 0.0,
+        });
+
+        p.set_turn(5.0);
+        game.process(0.5);
+        assert!((p.get_direction() - 45.0).abs() < 1e-9);
+
+        p.set_turn(-0.5);
+        game.process(1.0);
+        assert!(p.get_direction().abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_newtonian_drag_and_max_speed() {
+        let (mut p, mut game) = get_newtonian_player(NewtonianConfig {
+            acceleration: 1000.0,
+            max_angular_velocity: 90.0,
+            drag: 1.0,
+        });
+
+        p.set_thrust(1.0);
+        game.process(0.1);
+        assert!((p.get_speed() - 20.0).abs() < 1e-9);
+
+        p.set_thrust(0.0);
+        game.process(1.0);
+        assert!((p.get_speed() - 20.0 * (-1.0f64).exp()).abs() < 1e-9);
+        let (vx, vy) = p.get_velocity();
+        assert!(vx.abs() < 1e-9);
+        assert!((vy - p.get_speed()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_spawn_player() {
+        let game = Game::new(
+            Map::new(100.0, 100.0, 0, 0.0, 1),
+            GameConfig {
+                seed: Some(SEED),
+                spawn: SpawnConfig {
+                    min_distance: 20.0,
+                    avoid_firing_lines: true,
+                },
+                ..Default::default()
+            },
+        );
+        let players: Vec<_> = (0..2)
+            .map(|_| Player::new(0.0, 0.0, 5.0, 10.0, 60.0, 1, MISSILE_SPEED))
+            .collect();
+        for p in players.iter() {
+            game.spawn_player(p).unwrap();
+        }
+        let (a, b) = (&players[0], &players[1]);
+        assert_eq!(game.lock().unwrap().players.len(), 2);
+        assert!((a.get_x() - b.get_x()).hypot(a.get_y() - b.get_y()) >= 30.0);
+
+        // There's no room for a huge ship, so it's left out
+        let huge = Player::new(0.0, 0.0, 60.0, 10.0, 60.0, 1, MISSILE_SPEED);
+        assert_eq!(game.spawn_player(&huge), Err(SpawnError::TooLarge));
+        assert_eq!(game.lock().unwrap().players.len(), 2);
+    }
 }
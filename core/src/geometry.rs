@@ -0,0 +1,214 @@
+/// A circle shaped obstacle
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Circle {
+    pub x: f64,
+    pub y: f64,
+    pub r: f64,
+}
+
+/// Finds the distance along the ray with the unit direction `(dx, dy)` to the circle.
+///
+/// Returns `0.0` if the ray starts inside the circle and `None` if it misses or touches it
+/// tangentially.
+pub fn ray_circle_intersection(x: f64, y: f64, dx: f64, dy: f64, circle: &Circle) -> Option<f64> {
+    let (fx, fy) = (x - circle.x, y - circle.y);
+    let c = fx * fx + fy * fy - circle.r * circle.r;
+    if c < 0.0 {
+        return Some(0.0);
+    }
+
+    let b = fx * dx + fy * dy;
+    if b > 0.0 {
+        // The circle is behind the ray
+        return None;
+    }
+    let discriminant = b * b - c;
+    if discriminant <= 0.0 {
+        return None;
+    }
+    Some(-b - discriminant.sqrt())
+}
+
+/// Finds the distance along the ray with the unit direction `(dx, dy)` to the border of the
+/// `[0; width] x [0; height]` rectangle and the unit normal of the border pointing inside.
+pub fn ray_rect_exit(
+    x: f64,
+    y: f64,
+    dx: f64,
+    dy: f64,
+    width: f64,
+    height: f64,
+) -> (f64, (f64, f64)) {
+    let (tx, nx) = if dx > 0.0 {
+        ((width - x) / dx, -1.0)
+    } else if dx < 0.0 {
+        (-x / dx, 1.0)
+    } else {
+        (f64::INFINITY, 0.0)
+    };
+    let (ty, ny) = if dy > 0.0 {
+        ((height - y) / dy, -1.0)
+    } else if dy < 0.0 {
+        (-y / dy, 1.0)
+    } else {
+        (f64::INFINITY, 0.0)
+    };
+
+    if tx < ty {
+        (tx.max(0.0), (nx, 0.0))
+    } else {
+        (ty.max(0.0), (0.0, ny))
+    }
+}
+
+/// Finds where the segment from `(x0, y0)` to `(x1, y1)` enters the circle.
+///
+/// Returns the fraction of the segment in `[0; 1]` at the first contact, or `0.0` if the segment
+/// starts inside the circle. Touching the circle tangentially is not a hit.
+pub fn segment_circle_intersection(
+    x0: f64,
+    y0: f64,
+    x1: f64,
+    y1: f64,
+    cx: f64,
+    cy: f64,
+    r: f64,
+) -> Option<f64> {
+    let (fx, fy) = (x0 - cx, y0 - cy);
+    let c = fx * fx + fy * fy - r * r;
+    if c < 0.0 {
+        return Some(0.0);
+    }
+
+    let (dx, dy) = (x1 - x0, y1 - y0);
+    let a = dx * dx + dy * dy;
+    if a == 0.0 {
+        return None;
+    }
+    let b = 2.0 * (fx * dx + fy * dy);
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant <= 0.0 {
+        return None;
+    }
+
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    if (0.0..=1.0).contains(&t) {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+/// Finds where the segment from `(x0, y0)` to `(x1, y1)` leaves the `[0; width] x [0; height]`
+/// rectangle. Returns `None` if the segment ends inside or on the border.
+pub fn segment_rect_exit(
+    x0: f64,
+    y0: f64,
+    x1: f64,
+    y1: f64,
+    width: f64,
+    height: f64,
+) -> Option<f64> {
+    let mut exit: Option<f64> = None;
+    let mut update = |t: f64| {
+        let t = t.clamp(0.0, 1.0);
+        exit = Some(exit.map_or(t, |e: f64| e.min(t)));
+    };
+
+    if x1 < 0.0 {
+        update(x0 / (x0 - x1));
+    } else if x1 > width {
+        update((width - x0) / (x1 - x0));
+    }
+    if y1 < 0.0 {
+        update(y0 / (y0 - y1));
+    } else if y1 > height {
+        update((height - y0) / (y1 - y0));
+    }
+    exit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ray_circle_intersection, ray_rect_exit, segment_circle_intersection, segment_rect_exit,
+        Circle,
+    };
+
+    #[test]
+    fn test_segment_through_circle() {
+        let t = segment_circle_intersection(0.0, 0.0, 100.0, 0.0, 50.0, 0.0, 10.0);
+        assert_eq!(t, Some(0.4));
+    }
+
+    #[test]
+    fn test_segment_misses_circle() {
+        assert_eq!(
+            segment_circle_intersection(0.0, 0.0, 100.0, 0.0, 50.0, 20.0, 10.0),
+            None
+        );
+        // Tangent contact
+        assert_eq!(
+            segment_circle_intersection(0.0, 0.0, 100.0, 0.0, 50.0, 10.0, 10.0),
+            None
+        );
+        // The circle is behind the segment's end
+        assert_eq!(
+            segment_circle_intersection(0.0, 0.0, 30.0, 0.0, 50.0, 0.0, 10.0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_segment_starts_inside_circle() {
+        let t = segment_circle_intersection(50.0, 0.0, 100.0, 0.0, 50.0, 0.0, 10.0);
+        assert_eq!(t, Some(0.0));
+    }
+
+    #[test]
+    fn test_segment_rect_exit() {
+        assert_eq!(
+            segment_rect_exit(50.0, 50.0, 150.0, 50.0, 100.0, 100.0),
+            Some(0.5)
+        );
+        assert_eq!(
+            segment_rect_exit(50.0, 50.0, 50.0, -50.0, 100.0, 100.0),
+            Some(0.5)
+        );
+        assert_eq!(
+            segment_rect_exit(50.0, 50.0, 100.0, 100.0, 100.0, 100.0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ray_circle_intersection() {
+        let circle = Circle {
+            x: 50.0,
+            y: 0.0,
+            r: 10.0,
+        };
+        assert_eq!(
+            ray_circle_intersection(0.0, 0.0, 1.0, 0.0, &circle),
+            Some(40.0)
+        );
+        assert_eq!(ray_circle_intersection(0.0, 0.0, -1.0, 0.0, &circle), None);
+        assert_eq!(ray_circle_intersection(0.0, 10.0, 1.0, 0.0, &circle), None);
+        assert_eq!(
+            ray_circle_intersection(45.0, 0.0, 1.0, 0.0, &circle),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn test_ray_rect_exit() {
+        assert_eq!(
+            ray_rect_exit(25.0, 50.0, 1.0, 0.0, 100.0, 100.0),
+            (75.0, (-1.0, 0.0))
+        );
+        assert_eq!(
+            ray_rect_exit(25.0, 50.0, 0.0, -1.0, 100.0, 100.0),
+            (50.0, (0.0, 1.0))
+        );
+    }
+}
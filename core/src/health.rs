@@ -0,0 +1,88 @@
+/// Regenerating shield that takes damage before the hull
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ShieldConfig {
+    pub capacity: f64,
+    /// Shield restored per second
+    pub regeneration: f64,
+}
+
+/// Hit points and shield of a ship
+#[derive(Clone, Debug, PartialEq)]
+pub struct Health {
+    pub shield_config: Option<ShieldConfig>,
+    pub hp: f64,
+    pub shield: f64,
+}
+
+impl Health {
+    /// Creates a ship with the full hull and shield
+    pub fn new(hp: f64, shield_config: Option<ShieldConfig>) -> Self {
+        Health {
+            shield_config,
+            hp,
+            shield: shield_config.map_or(0.0, |s| s.capacity),
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.hp > 0.0
+    }
+
+    /// Regenerates the shield
+    pub fn tick(&mut self, timedelta: f64) {
+        if let Some(shield) = self.shield_config {
+            self.shield = (self.shield + shield.regeneration * timedelta).min(shield.capacity);
+        }
+    }
+
+    /// Takes the damage by the shield first and then by the hull. Returns the damage actually
+    /// taken, it's less than `damage` if the ship runs out of hit points.
+    pub fn damage(&mut self, damage: f64) -> f64 {
+        let absorbed = damage.min(self.shield);
+        self.shield -= absorbed;
+        let taken = (damage - absorbed).min(self.hp);
+        self.hp -= taken;
+        absorbed + taken
+    }
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self::new(1.0, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Health, ShieldConfig};
+
+    #[test]
+    fn test_damage() {
+        let mut health = Health::new(3.0, None);
+        assert_eq!(health.damage(1.0), 1.0);
+        assert!(health.is_alive());
+        assert_eq!(health.damage(5.0), 2.0);
+        assert!(!health.is_alive());
+    }
+
+    #[test]
+    fn test_shield() {
+        let mut health = Health::new(
+            3.0,
+            Some(ShieldConfig {
+                capacity: 2.0,
+                regeneration: 1.0,
+            }),
+        );
+        assert_eq!(health.damage(3.0), 3.0);
+        assert_eq!((health.hp, health.shield), (2.0, 0.0));
+
+        health.tick(1.5);
+        assert_eq!(health.shield, 1.5);
+        health.tick(10.0);
+        assert_eq!(health.shield, 2.0);
+
+        assert_eq!(health.damage(1.0), 1.0);
+        assert_eq!((health.hp, health.shield), (2.0, 1.0));
+    }
+}
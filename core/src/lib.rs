@@ -1,8 +1,38 @@
+pub mod config;
+pub mod events;
 pub mod game;
+pub mod geometry;
+pub mod health;
 pub mod map;
 pub mod player;
+pub mod ray_casting;
 pub mod ray_marching;
+pub mod sensors;
+pub mod shape;
+pub mod snapshot;
+pub mod spatial;
+pub mod spawn;
+pub mod validation;
+pub mod weapon;
+pub mod world;
 
-pub use game::{Game, GameTrait, RegisterPlayer};
-pub use map::Map;
-pub use player::{Player, PlayerStatus, PlayerTrait, ViewHit, ViewTrait};
+pub use config::{ConfigError, GameConfig};
+pub use events::{Event, GameEvent};
+pub use game::{
+    CollisionResponse, FlightModel, Game, GameTrait, NewtonianConfig, RegisterPlayer, Tiebreak,
+};
+pub use health::ShieldConfig;
+#[cfg(feature = "serde")]
+pub use map::MapFileError;
+pub use map::{Map, SpawnPoint, Symmetry};
+pub use player::{
+    Player, PlayerStatus, PlayerTrait, RayObservation, SelfState, ShipObservation, ViewHit,
+    ViewTrait,
+};
+pub use sensors::{RadarConfig, RadarContact, RadarTarget, SensorNoise};
+pub use shape::Shape;
+pub use snapshot::{GameSnapshot, RestoreError};
+pub use spawn::{SpawnConfig, SpawnError};
+pub use validation::MapReport;
+pub use weapon::{EnergyConfig, FireError, WeaponConfig};
+pub use world::{Action, ActionError, PlayerId, World};
@@ -1,28 +1,161 @@
+#[cfg(feature = "serde")]
+use std::error::Error;
+#[cfg(feature = "serde")]
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::fs;
+#[cfg(feature = "serde")]
+use std::io;
+#[cfg(feature = "serde")]
+use std::path::Path;
+
 use noise::{NoiseFn, Perlin};
 use rand::prelude::*;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 
-#[derive(Copy, Clone, Debug)]
+use super::geometry::Circle;
+use super::shape::Shape;
+use super::spatial::SpatialGrid;
+use super::spawn::{random_point, SpawnError};
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Barrier {
     pub x: f64,
     pub y: f64,
-    pub r: f64,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub shape: Shape,
+}
+
+/// The bounding circle of the barrier
+impl From<&Barrier> for Circle {
+    fn from(value: &Barrier) -> Self {
+        Circle {
+            x: value.x,
+            y: value.y,
+            r: value.bounding_radius(),
+        }
+    }
 }
 
-#[derive(Clone, Debug)]
+// Random positions tried for a barrier of `new_with_gaps` before it's dropped
+const MAX_PLACEMENT_ATTEMPTS: usize = 100;
+
+/// Place where a player starts the game
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpawnPoint {
+    pub x: f64,
+    pub y: f64,
+    pub direction: f64,
+}
+
+/// Symmetry of a generated map, every spawn point sees the same map
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Symmetry {
+    /// Reflection across the vertical center line, spawn points come in mirrored pairs
+    Mirror,
+    /// N-fold rotation around the center, every player has its own sector
+    Rotational,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Map {
     pub width: f64,
     pub height: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub barriers: Vec<Barrier>,
+    /// Seed the map was generated with, hand-authored maps may leave it out
+    #[cfg_attr(feature = "serde", serde(default))]
     pub seed: u64,
+    /// Fair starting places of players, random free points are used if it's empty
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub spawn_points: Vec<SpawnPoint>,
+}
+
+/// Why a map couldn't be read or written
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum MapFileError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    TomlRead(toml::de::Error),
+    TomlWrite(toml::ser::Error),
+    /// The file's extension is neither `json` nor `toml`
+    UnknownFormat,
+    /// Index of a barrier whose polygon isn't convex
+    InvalidPolygon(usize),
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for MapFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapFileError::Io(err) => write!(f, "{}", err),
+            MapFileError::Json(err) => write!(f, "{}", err),
+            MapFileError::TomlRead(err) => write!(f, "{}", err),
+            MapFileError::TomlWrite(err) => write!(f, "{}", err),
+            MapFileError::UnknownFormat => write!(f, "map files must be .json or .toml"),
+            MapFileError::InvalidPolygon(i) => write!(f, "barrier {} is not a convex polygon", i),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Error for MapFileError {}
+
+#[cfg(feature = "serde")]
+impl From<io::Error> for MapFileError {
+    fn from(err: io::Error) -> Self {
+        MapFileError::Io(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for MapFileError {
+    fn from(err: serde_json::Error) -> Self {
+        MapFileError::Json(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<toml::de::Error> for MapFileError {
+    fn from(err: toml::de::Error) -> Self {
+        MapFileError::TomlRead(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<toml::ser::Error> for MapFileError {
+    fn from(err: toml::ser::Error) -> Self {
+        MapFileError::TomlWrite(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+enum MapFormat {
+    Json,
+    Toml,
+}
+
+#[cfg(feature = "serde")]
+impl MapFormat {
+    fn from_path(path: &Path) -> Result<Self, MapFileError> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("json") => Ok(MapFormat::Json),
+            Some(e) if e.eq_ignore_ascii_case("toml") => Ok(MapFormat::Toml),
+            _ => Err(MapFileError::UnknownFormat),
+        }
+    }
 }
 
 impl Map {
     pub fn new(
         width: f64,
         height: f64,
-        barriers_amount: u8,
+        barriers_amount: usize,
         max_barrier_radius: f64,
         seed: u64,
     ) -> Self {
@@ -36,7 +169,7 @@ impl Map {
                 let y: f64 = rng.gen_range(0.0..height);
                 let noise_value = perlin.get([x * noise_scale, y * noise_scale]);
                 let r = (noise_value / 2.0 + 0.5) * max_barrier_radius; // Noise normalization from -1..1 to 0..max_barrier_radius
-                Barrier { x, y, r }
+                Barrier::circle(x, y, r)
             })
             .collect();
 
@@ -45,43 +178,233 @@ impl Map {
             height,
             barriers,
             seed,
+            spawn_points: Vec::new(),
         }
     }
 
-    pub fn new_without_seed(
+    /// Generates a symmetric map with spawn points for `players`. The mirror symmetry rounds
+    /// `players` up to an even number and the rotational one uses `players` as its order.
+    /// `barriers_amount` is rounded down to a multiple of the order. Spawn points are kept
+    /// `max_barrier_radius` away from barriers.
+    pub fn new_symmetric(
         width: f64,
         height: f64,
-        barriers_amount: u8,
+        barriers_amount: usize,
         max_barrier_radius: f64,
+        seed: u64,
+        symmetry: Symmetry,
+        players: usize,
     ) -> Self {
-        let seed: u64 = rand::random::<u64>();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let perlin = Perlin::new(seed as u32);
+        let noise_scale = 0.1;
+        let (cx, cy) = (width / 2.0, height / 2.0);
 
-        Self::new(width, height, barriers_amount, max_barrier_radius, seed)
+        // Barriers are generated in one part of the map and copied to the others
+        let (order, spawn_points): (usize, Vec<SpawnPoint>) = match symmetry {
+            Symmetry::Mirror => {
+                let pairs = players.div_ceil(2).max(1);
+                let spawn_points = (0..pairs)
+                    .flat_map(|k| {
+                        let y = height * (k as f64 + 0.5) / pairs as f64;
+                        [
+                            SpawnPoint {
+                                x: width / 4.0,
+                                y,
+                                direction: 90.0,
+                            },
+                            SpawnPoint {
+                                x: width * 3.0 / 4.0,
+                                y,
+                                direction: 270.0,
+                            },
+                        ]
+                    })
+                    .collect();
+                (2, spawn_points)
+            }
+            Symmetry::Rotational => {
+                let order = players.max(1);
+                let distance = width.min(height) / 2.0 * 0.75;
+                let spawn_points = (0..order)
+                    .map(|k| {
+                        let angle = 360.0 * k as f64 / order as f64;
+                        let (x, y) = rotate(cx, cy + distance, cx, cy, angle);
+                        SpawnPoint {
+                            x,
+                            y,
+                            direction: (angle + 180.0) % 360.0,
+                        }
+                    })
+                    .collect();
+                (order, spawn_points)
+            }
+        };
+
+        let mut barriers = Vec::new();
+        for _ in 0..barriers_amount / order {
+            let (x, y) = match symmetry {
+                Symmetry::Mirror => (rng.gen_range(0.0..cx), rng.gen_range(0.0..height)),
+                Symmetry::Rotational => {
+                    // Uniform over the sector of the inscribed circle
+                    let distance = width.min(height) / 2.0 * rng.gen::<f64>().sqrt();
+                    let angle = rng.gen_range(0.0..360.0 / order as f64);
+                    rotate(cx, cy + distance, cx, cy, angle)
+                }
+            };
+            let noise_value = perlin.get([x * noise_scale, y * noise_scale]);
+            let mut r = (noise_value / 2.0 + 0.5) * max_barrier_radius;
+            // Copies are as far from the other spawn points, so checking this one is enough
+            for spawn in spawn_points.iter() {
+                let distance = (x - spawn.x).hypot(y - spawn.y);
+                r = r.min(distance - max_barrier_radius);
+            }
+            if r <= 0.0 {
+                continue;
+            }
+            match symmetry {
+                Symmetry::Mirror => {
+                    barriers.push(Barrier::circle(x, y, r));
+                    barriers.push(Barrier::circle(width - x, y, r));
+                }
+                Symmetry::Rotational => {
+                    for k in 0..order {
+                        let angle = 360.0 * k as f64 / order as f64;
+                        let (x, y) = rotate(x, y, cx, cy, angle);
+                        barriers.push(Barrier::circle(x, y, r));
+                    }
+                }
+            }
+        }
+
+        Map {
+            width,
+            height,
+            barriers,
+            seed,
+            spawn_points,
+        }
     }
 
-    pub fn get_free_point(&self, r: f64) -> (f64, f64) {
-        'outer: loop {
-            let x = rand::thread_rng().gen_range(r..self.width - r);
-            let y = rand::thread_rng().gen_range(r..self.height - r);
+    /// Generates a map like `new`, but barriers don't overlap and keep `min_gap` between each
+    /// other and the borders, so ships narrower than `min_gap` can reach any free place. Barriers
+    /// are shrunk to keep the gaps and dropped if there's no room for them.
+    pub fn new_with_gaps(
+        width: f64,
+        height: f64,
+        barriers_amount: usize,
+        max_barrier_radius: f64,
+        seed: u64,
+        min_gap: f64,
+    ) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let perlin = Perlin::new(seed as u32);
+        let noise_scale = 0.1;
+        let mut index = SpatialGrid::new(width, height, 2.0 * max_barrier_radius + min_gap);
+        let mut neighbours = Vec::new();
+
+        let mut barriers: Vec<Barrier> = Vec::new();
+        for _ in 0..barriers_amount {
+            for _ in 0..MAX_PLACEMENT_ATTEMPTS {
+                let x: f64 = rng.gen_range(0.0..width);
+                let y: f64 = rng.gen_range(0.0..height);
+                let noise_value = perlin.get([x * noise_scale, y * noise_scale]);
+                let mut r = (noise_value / 2.0 + 0.5) * max_barrier_radius;
 
-            for barrier in self.barriers.iter() {
-                // If there are no collisions, return `x` and `y`
-                let distance = ((x - barrier.x).powi(2) + (y - barrier.y).powi(2)).sqrt();
-                if distance >= (r + barrier.r) {
-                    break 'outer (x, y);
+                r = r.min(x.min(width - x).min(y).min(height - y) - min_gap);
+                index.query_circle(x, y, max_barrier_radius + min_gap, &mut neighbours);
+                for b in neighbours.iter().map(|&i| &barriers[i]) {
+                    r = r.min(b.distance(x, y) - min_gap);
+                }
+                if r > 0.0 {
+                    let barrier = Barrier::circle(x, y, r);
+                    index.insert(barriers.len(), &Circle::from(&barrier));
+                    barriers.push(barrier);
+                    break;
                 }
             }
         }
+
+        Map {
+            width,
+            height,
+            barriers,
+            seed,
+            spawn_points: Vec::new(),
+        }
+    }
+
+    pub fn new_without_seed(
+        width: f64,
+        height: f64,
+        barriers_amount: usize,
+        max_barrier_radius: f64,
+    ) -> Self {
+        let seed: u64 = rand::random::<u64>();
+
+        Self::new(width, height, barriers_amount, max_barrier_radius, seed)
+    }
+
+    /// Reads a map from a JSON or TOML file, the format is picked by the extension. Polygons must
+    /// be convex.
+    #[cfg(feature = "serde")]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, MapFileError> {
+        let path = path.as_ref();
+        let format = MapFormat::from_path(path)?;
+        let data = fs::read_to_string(path)?;
+        let map: Map = match format {
+            MapFormat::Json => serde_json::from_str(&data)?,
+            MapFormat::Toml => toml::from_str(&data)?,
+        };
+        if let Some(i) = map.barriers.iter().position(|b| !b.shape.is_valid()) {
+            return Err(MapFileError::InvalidPolygon(i));
+        }
+        Ok(map)
+    }
+
+    /// Writes the map to a JSON or TOML file, the format is picked by the extension. Numbers are
+    /// written exactly, so the map is read back identical.
+    #[cfg(feature = "serde")]
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), MapFileError> {
+        let path = path.as_ref();
+        let data = match MapFormat::from_path(path)? {
+            MapFormat::Json => serde_json::to_string_pretty(self)?,
+            MapFormat::Toml => toml::to_string(self)?,
+        };
+        fs::write(path, data)?;
+        Ok(())
     }
+
+    /// Builds the broad phase index of barriers, indices are the barriers' positions in the vector
+    pub fn build_index(&self) -> SpatialGrid {
+        let circles: Vec<Circle> = self.barriers.iter().map(Circle::from).collect();
+        SpatialGrid::from_circles(self.width, self.height, &circles)
+    }
+
+    /// Finds a random point where a ship of radius `r` is inside the map and clear of every
+    /// barrier
+    pub fn get_free_point<R: Rng>(&self, r: f64, rng: &mut R) -> Result<(f64, f64), SpawnError> {
+        random_point(self, r, rng, |_, _| true)
+    }
+}
+
+/// Rotates the point around `(cx, cy)` by `angle` degrees in the direction of player rotation
+pub(crate) fn rotate(x: f64, y: f64, cx: f64, cy: f64, angle: f64) -> (f64, f64) {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (dx, dy) = (x - cx, y - cy);
+    (cx + dx * cos + dy * sin, cy - dx * sin + dy * cos)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Map;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{rotate, Map, Shape, Symmetry};
 
     const WIDTH: f64 = 1000.0;
     const HEIGHT: f64 = 1500.0;
-    const BARRIERS_AMOUNT: u8 = 5;
+    const BARRIERS_AMOUNT: usize = 5;
     const MAX_BARRIER_RADIUS: f64 = 100.0;
     const SEED: u64 = 12345;
 
@@ -99,11 +422,11 @@ mod tests {
     #[test]
     fn test_barriers() {
         let m = make_map();
-        assert_eq!(m.barriers.len() as u8, BARRIERS_AMOUNT);
+        assert_eq!(m.barriers.len(), BARRIERS_AMOUNT);
         for b in m.barriers {
             assert!(b.x <= WIDTH);
             assert!(b.y <= HEIGHT);
-            assert!(b.r <= MAX_BARRIER_RADIUS);
+            assert!(b.bounding_radius() <= MAX_BARRIER_RADIUS);
         }
     }
 
@@ -129,7 +452,194 @@ mod tests {
         for (barrier1, barrier2) in map1.barriers.iter().zip(map2.barriers.iter()) {
             assert_eq!(barrier1.x, barrier2.x);
             assert_eq!(barrier1.y, barrier2.y);
-            assert_eq!(barrier1.r, barrier2.r);
+            assert_eq!(barrier1.shape, barrier2.shape);
+        }
+    }
+
+    #[test]
+    fn test_free_point_with_seed() {
+        let m = make_map();
+        let point1 = m
+            .get_free_point(10.0, &mut StdRng::seed_from_u64(SEED))
+            .unwrap();
+        let point2 = m
+            .get_free_point(10.0, &mut StdRng::seed_from_u64(SEED))
+            .unwrap();
+        assert_eq!(point1, point2);
+    }
+
+    #[test]
+    fn test_many_barriers() {
+        let m = Map::new(WIDTH, HEIGHT, 5000, MAX_BARRIER_RADIUS, SEED);
+        assert_eq!(m.barriers.len(), 5000);
+    }
+
+    fn assert_contains(m: &Map, x: f64, y: f64, r: f64) {
+        assert!(
+            m.barriers.iter().any(|b| (b.x - x).abs() < 1e-9
+                && (b.y - y).abs() < 1e-9
+                && b.shape == Shape::Circle { r }),
+            "no barrier at ({}, {})",
+            x,
+            y
+        );
+    }
+
+    fn assert_spawn_points_are_free(m: &Map, max_barrier_radius: f64) {
+        for spawn in m.spawn_points.iter() {
+            for b in m.barriers.iter() {
+                assert!(b.distance(spawn.x, spawn.y) >= max_barrier_radius - 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mirror_symmetry() {
+        let m = Map::new_symmetric(
+            WIDTH,
+            HEIGHT,
+            100,
+            MAX_BARRIER_RADIUS,
+            SEED,
+            Symmetry::Mirror,
+            3,
+        );
+        assert_eq!(m.spawn_points.len(), 4);
+        assert!(!m.barriers.is_empty() && m.barriers.len() <= 100);
+        for b in m.barriers.iter() {
+            assert_contains(&m, WIDTH - b.x, b.y, b.bounding_radius());
+        }
+        for pair in m.spawn_points.chunks(2) {
+            assert_eq!(pair[0].x, WIDTH - pair[1].x);
+            assert_eq!(pair[0].y, pair[1].y);
+            assert_eq!(pair[0].direction, 360.0 - pair[1].direction);
+        }
+        assert_spawn_points_are_free(&m, MAX_BARRIER_RADIUS);
+    }
+
+    #[test]
+    fn test_rotational_symmetry() {
+        let m = Map::new_symmetric(
+            WIDTH,
+            WIDTH,
+            100,
+            MAX_BARRIER_RADIUS,
+            SEED,
+            Symmetry::Rotational,
+            3,
+        );
+        let c = WIDTH / 2.0;
+        assert!(!m.barriers.is_empty());
+        assert_eq!(m.spawn_points.len(), 3);
+        for b in m.barriers.iter() {
+            let (x, y) = rotate(b.x, b.y, c, c, 120.0);
+            assert_contains(&m, x, y, b.bounding_radius());
         }
+        for spawn in m.spawn_points.iter() {
+            let distance = (spawn.x - c).hypot(spawn.y - c);
+            assert!((distance - WIDTH * 0.375).abs() < 1e-9);
+            // Facing the center
+            let (sin, cos) = spawn.direction.to_radians().sin_cos();
+            assert!((spawn.x + sin * distance - c).abs() < 1e-9);
+            assert!((spawn.y + cos * distance - c).abs() < 1e-9);
+        }
+        assert_spawn_points_are_free(&m, MAX_BARRIER_RADIUS);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_file_round_trip() {
+        let m = make_map();
+        let dir = std::env::temp_dir();
+        for name in ["space_drive_map.json", "space_drive_map.toml"] {
+            let path = dir.join(name);
+            m.to_file(&path).unwrap();
+            let loaded = Map::from_file(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(loaded, m);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_hand_authored_file() {
+        use super::{Barrier, MapFileError};
+
+        let path = std::env::temp_dir().join("space_drive_arena.toml");
+        std::fs::write(
+            &path,
+            "width = 100\nheight = 50\n\n[[barriers]]\nx = 50\ny = 25\nr = 10\n",
+        )
+        .unwrap();
+        let m = Map::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(m.width, 100.0);
+        assert_eq!(m.height, 50.0);
+        assert_eq!(m.barriers, vec![Barrier::circle(50.0, 25.0, 10.0)]);
+
+        assert!(matches!(
+            Map::from_file("arena.yaml"),
+            Err(MapFileError::UnknownFormat)
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_shaped_barriers_file() {
+        use super::{Barrier, MapFileError};
+
+        let path = std::env::temp_dir().join("space_drive_walls.toml");
+        std::fs::write(
+            &path,
+            "width = 100\nheight = 50\n\n\
+             [[barriers]]\nx = 50\ny = 25\nwidth = 40\nheight = 4\nangle = 90\n\n\
+             [[barriers]]\nx = 20\ny = 25\nlength = 10\nr = 2\n\n\
+             [[barriers]]\nx = 80\ny = 25\npoints = [[0, 0], [10, 0], [0, 10]]\n\n\
+             [[barriers]]\nx = 80\ny = 10\nr = 3\n",
+        )
+        .unwrap();
+        let m = Map::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let shapes: Vec<Shape> = m.barriers.iter().map(|b| b.shape.clone()).collect();
+        assert_eq!(
+            shapes,
+            vec![
+                Shape::Rect {
+                    width: 40.0,
+                    height: 4.0,
+                    angle: 90.0
+                },
+                Shape::Capsule {
+                    length: 10.0,
+                    r: 2.0,
+                    angle: 0.0
+                },
+                Shape::Polygon {
+                    points: vec![(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)]
+                },
+                Shape::Circle { r: 3.0 },
+            ]
+        );
+        assert_eq!(m.barriers[3], Barrier::circle(80.0, 10.0, 3.0));
+
+        for name in ["space_drive_walls.json", "space_drive_walls_copy.toml"] {
+            let path = std::env::temp_dir().join(name);
+            m.to_file(&path).unwrap();
+            let loaded = Map::from_file(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(loaded, m);
+        }
+
+        let path = std::env::temp_dir().join("space_drive_concave.toml");
+        std::fs::write(
+            &path,
+            "width = 100\nheight = 50\n\n\
+             [[barriers]]\nx = 20\ny = 25\nr = 2\n\n\
+             [[barriers]]\nx = 80\ny = 25\npoints = [[0, 0], [10, 0], [2, 2], [0, 10]]\n",
+        )
+        .unwrap();
+        let result = Map::from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(MapFileError::InvalidPolygon(1))));
     }
 }
@@ -2,55 +2,139 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, Weak};
 
 use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use super::config::GameConfig;
+use super::events::{Event, EventLog};
+use super::game::{player_velocity, Game};
+use super::geometry::Circle;
+use super::health::Health;
+use super::map::Map;
+use super::ray_casting::{cast_ray, RayHitKind};
+use super::sensors::{add_noise, noise_seed, scan, RadarContact, SensorNoise};
+use super::spatial::SpatialGrid;
+use super::weapon::{FireError, Weapon};
+
+/// Allocates ids of players and missiles, unique within a game
+#[derive(Debug)]
+pub struct IdAllocator(AtomicUsize);
+
+impl IdAllocator {
+    pub fn new() -> Self {
+        IdAllocator(AtomicUsize::new(1))
+    }
+
+    pub fn next_id(&self) -> usize {
+        self.0.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Id the next call of `next_id` returns
+    pub fn peek(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
 
-use super::game::Game;
-use super::ray_marching::{ray_marching, RayHit};
+    pub fn reset(&self, next_id: usize) {
+        self.0.store(next_id, Ordering::Relaxed);
+    }
+}
 
-fn get_id() -> usize {
-    static COUNTER: AtomicUsize = AtomicUsize::new(1);
-    COUNTER.fetch_add(1, Ordering::Relaxed)
+impl Clone for IdAllocator {
+    fn clone(&self) -> Self {
+        IdAllocator(AtomicUsize::new(self.peek()))
+    }
 }
 
-#[derive(Debug, PartialEq)]
+impl Default for IdAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum PlayerStatus {
     Win,
+    /// The player is alive when the time limit expires, but another team wins
+    Lose,
+    /// The player is alive when the time limit expires and the match is a draw
+    Draw,
     InGame,
     KilledBy(usize),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ViewHit {
     Barrier(f64),
     Border(f64),
     Enemy(f64),
+    Ally(f64),
+    /// Missile of another player, it's seen only if `missiles_visible` is set in the config
+    Missile {
+        distance: f64,
+        player_id: usize,
+        direction: f64,
+    },
+    /// The reading of the ray is lost by the sensor noise
+    Unknown,
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct Missile {
     pub x: f64,
     pub y: f64,
     pub direction: f64,
     pub id: usize,
     pub player_id: usize,
+    pub team: usize,
     pub speed: f64,
+    /// Distance flown since the launch
+    pub traveled: f64,
+    /// Time since the launch
+    pub age: f64,
 }
 
+#[derive(Clone)]
 pub struct Player {
     pub x: f64,
     pub y: f64,
     pub r: f64,
     pub direction: f64,
     pub speed: f64,
-    max_speed: f64,
+    /// Velocity of the last step, it's integrated from thrust in the Newtonian flight model
+    pub vx: f64,
+    pub vy: f64,
+    /// Thrust command in `[-1; 1]` of the Newtonian flight model
+    pub thrust: f64,
+    /// Turn command in `[-1; 1]` of the max angular velocity of the Newtonian flight model
+    pub turn: f64,
+    pub(crate) max_speed: f64,
     view_angle: f64,
     rays_amount: u16,
     game: Weak<Mutex<Game>>,
     missiles: Weak<Mutex<Vec<Missile>>>,
+    events: Weak<Mutex<EventLog>>,
+    ids: Weak<IdAllocator>,
+    /// Assigned by the game on registration
     pub id: usize,
+    // Solo players are teams of their own
+    pub(crate) team: Option<usize>,
     missile_speed: f64,
+    /// Loaded by the game on registration
+    pub weapon: Weapon,
+    /// Set up by the game on registration
+    pub health: Health,
+    /// Total damage dealt to other ships
+    pub damage_dealt: f64,
+    /// Amount of ships destroyed by the final blow of this one
+    pub kills: usize,
     pub status: PlayerStatus,
+    /// Overrides the sensor noise of the game for this player
+    pub sensor_noise: Option<SensorNoise>,
+    // The direction is picked by the game's RNG on registration
+    pub(crate) random_direction: bool,
 }
 
 impl Player {
+    /// Creates a player without direction, the game picks a random one on registration
     pub fn new(
         x: f64,
         y: f64,
@@ -60,24 +144,38 @@ impl Player {
         rays_amount: u16,
         missile_speed: f64,
     ) -> Arc<Mutex<Self>> {
-        let player = Player {
+        let player = Self::new_plain(
             x,
             y,
             r,
-            direction: rand::thread_rng().gen_range(-180f64..180f64),
-            speed: 0.0,
             max_speed,
             view_angle,
             rays_amount,
-            game: Weak::new(),
-            missiles: Weak::new(),
-            id: get_id(),
+            None,
             missile_speed,
-            status: PlayerStatus::InGame,
-        };
+        );
         Arc::new(Mutex::new(player))
     }
 
+    /// Creates a player with the physics constants of the game config
+    pub fn from_config(
+        x: f64,
+        y: f64,
+        view_angle: f64,
+        rays_amount: u16,
+        config: &GameConfig,
+    ) -> Arc<Mutex<Self>> {
+        Self::new(
+            x,
+            y,
+            config.player_radius,
+            config.player_max_speed,
+            view_angle,
+            rays_amount,
+            config.missile_speed,
+        )
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn new_with_direction(
         x: f64,
@@ -89,43 +187,304 @@ impl Player {
         direction: f64,
         missile_speed: f64,
     ) -> Arc<Mutex<Self>> {
-        let player = Player {
+        let player = Self::new_plain(
             x,
             y,
             r,
-            direction,
+            max_speed,
+            view_angle,
+            rays_amount,
+            Some(direction),
+            missile_speed,
+        );
+        Arc::new(Mutex::new(player))
+    }
+
+    /// Creates a player that isn't shared, it's meant to be added to a `World`. The world picks
+    /// a random direction if it's not set.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_plain(
+        x: f64,
+        y: f64,
+        r: f64,
+        max_speed: f64,
+        view_angle: f64,
+        rays_amount: u16,
+        direction: Option<f64>,
+        missile_speed: f64,
+    ) -> Self {
+        Player {
+            x,
+            y,
+            r,
+            direction: direction.unwrap_or(0.0),
             speed: 0.0,
+            vx: 0.0,
+            vy: 0.0,
+            thrust: 0.0,
+            turn: 0.0,
             max_speed,
             view_angle,
             rays_amount,
             game: Weak::new(),
             missiles: Weak::new(),
-            id: get_id(),
+            events: Weak::new(),
+            ids: Weak::new(),
+            id: 0,
+            team: None,
             missile_speed,
+            weapon: Weapon::default(),
+            health: Health::default(),
+            damage_dealt: 0.0,
+            kills: 0,
             status: PlayerStatus::InGame,
+            sensor_noise: None,
+            random_direction: direction.is_none(),
+        }
+    }
+
+    pub fn circle(&self) -> Circle {
+        Circle {
+            x: self.x,
+            y: self.y,
+            r: self.r,
+        }
+    }
+
+    pub fn mount_game(&mut self, game_ref: &Arc<Mutex<Game>>, game: &mut Game) {
+        self.game = Arc::downgrade(game_ref);
+        self.missiles = Arc::downgrade(&game.missiles);
+        self.events = Arc::downgrade(&game.events);
+        self.ids = Arc::downgrade(&game.ids);
+        self.join(&game.config, &game.ids, &mut game.rng);
+    }
+
+    /// Sets the player up for a game on registration
+    pub(crate) fn join(&mut self, config: &GameConfig, ids: &IdAllocator, rng: &mut StdRng) {
+        self.id = ids.next_id();
+        self.weapon = Weapon::new(config.weapon);
+        self.health = Health::new(config.player_hp, config.shield);
+        if self.random_direction {
+            self.direction = rng.gen_range(-180f64..180f64);
+            self.random_direction = false;
+        }
+    }
+
+    /// State of the player in a game on the map at the game time
+    pub fn self_state(&self, map: &Map, time: f64) -> SelfState {
+        SelfState {
+            x: self.x,
+            y: self.y,
+            normalized_x: self.x / map.width,
+            normalized_y: self.y / map.height,
+            direction: self.direction,
+            speed: self.speed,
+            cooldown: self.weapon.cooldown,
+            ammo: self.weapon.ammo,
+            energy: self.weapon.energy,
+            hp: self.health.hp,
+            shield: self.health.shield,
+            time,
+        }
+    }
+
+    /// Spends a shot and returns the launched missile with the event of the shot
+    pub(crate) fn shoot(&mut self, ids: &IdAllocator) -> Result<(Missile, Event), FireError> {
+        if self.status != PlayerStatus::InGame {
+            return Err(FireError::Inactive);
+        }
+        self.weapon.shoot()?;
+        let missile = Missile {
+            x: self.x,
+            y: self.y,
+            direction: self.direction,
+            id: ids.next_id(),
+            player_id: self.id,
+            team: self.get_team(),
+            speed: self.missile_speed,
+            traveled: 0.0,
+            age: 0.0,
         };
-        Arc::new(Mutex::new(player))
+        let event = Event::ShotFired {
+            missile_id: missile.id,
+            player_id: self.id,
+            x: self.x,
+            y: self.y,
+            direction: self.direction,
+        };
+        Ok((missile, event))
     }
+}
+
+/// What a player knows about itself
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SelfState {
+    pub x: f64,
+    pub y: f64,
+    /// Position divided by the map size, it's in `[0; 1]`
+    pub normalized_x: f64,
+    pub normalized_y: f64,
+    pub direction: f64,
+    pub speed: f64,
+    /// Time left until the weapon is ready
+    pub cooldown: f64,
+    /// Shots left, unlimited if it's not set
+    pub ammo: Option<u32>,
+    pub energy: f64,
+    pub hp: f64,
+    pub shield: f64,
+    /// Elapsed game time in seconds
+    pub time: f64,
+}
 
-    pub fn mount_game(&mut self, game: &Arc<Mutex<Game>>) {
-        self.game = Arc::downgrade(game);
-        self.missiles = Arc::downgrade(&game.lock().unwrap().missiles);
+/// What a ray of the extended view hits
+#[derive(Clone, Debug, PartialEq)]
+pub struct RayObservation {
+    pub hit: ViewHit,
+    /// Direction of the ray in degrees
+    pub angle: f64,
+    /// Direction of the ray relative to the player's direction
+    pub relative_angle: f64,
+    /// Id of the hit player or missile or index of the hit barrier in `map.barriers`, borders
+    /// have no id
+    pub id: Option<usize>,
+    /// Unit normal of the hit surface, it points toward the player
+    pub normal: (f64, f64),
+    /// Set if the ray hits a ship
+    pub ship: Option<ShipObservation>,
+}
+
+/// Motion of a ship hit by a ray
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ShipObservation {
+    /// Velocity of the ship relative to the player's one
+    pub velocity: (f64, f64),
+    /// Direction of the ship in degrees
+    pub direction: f64,
+}
+
+/// Sends the rays of the player, `others` are the other players of the game
+pub(crate) fn observe(
+    player: &Player,
+    others: &[&Player],
+    missiles: &[Missile],
+    map: &Map,
+    barriers_index: &SpatialGrid,
+    config: &GameConfig,
+) -> Vec<RayObservation> {
+    let team = player.get_team();
+    let others: Vec<&Player> = others
+        .iter()
+        .copied()
+        .filter(|p| p.status == PlayerStatus::InGame)
+        .collect();
+    // Own missiles start inside the ship, so they are never seen
+    let missiles: Vec<&Missile> = missiles
+        .iter()
+        .filter(|m| config.missiles_visible && m.player_id != player.id)
+        .collect();
+
+    // Missiles are cast as circles after the players
+    let circles: Vec<Circle> = others
+        .iter()
+        .map(|p| p.circle())
+        .chain(missiles.iter().map(|m| Circle {
+            x: m.x,
+            y: m.y,
+            r: config.missile_radius,
+        }))
+        .collect();
+    let (vx, vy) = player_velocity(player, config.flight_model);
+
+    let mut res = Vec::new();
+    for i in 0..player.rays_amount {
+        let angle_offset = if player.rays_amount > 1 {
+            player.view_angle / ((player.rays_amount - 1) as f64)
+        } else {
+            player.view_angle
+        };
+        let norm_i: f64 = (i as i16 - (player.rays_amount as i16 / 2)) as f64; // Example: if N_RAYS = 7 and i is [0;7), then norm_i will be -[3;3].
+        let relative_angle = norm_i * angle_offset;
+        let ray_direction = player.direction + relative_angle;
+        let ray_hit = cast_ray(
+            map,
+            barriers_index,
+            &circles,
+            player.x,
+            player.y,
+            ray_direction,
+            config.ray_cast_backend,
+        );
+
+        let distance = ray_hit.distance - player.r;
+        let (hit, id, ship) = match ray_hit.kind {
+            RayHitKind::Barrier(j) => (ViewHit::Barrier(distance), Some(j), None),
+            RayHitKind::Border => (ViewHit::Border(distance), None, None),
+            RayHitKind::Player(j) if j >= others.len() => {
+                let missile = missiles[j - others.len()];
+                let hit = ViewHit::Missile {
+                    distance,
+                    player_id: missile.player_id,
+                    direction: missile.direction,
+                };
+                (hit, Some(missile.id), None)
+            }
+            RayHitKind::Player(j) => {
+                let other = others[j];
+                let hit = if other.get_team() == team {
+                    ViewHit::Ally(distance)
+                } else {
+                    ViewHit::Enemy(distance)
+                };
+                let (other_vx, other_vy) = player_velocity(other, config.flight_model);
+                let ship = ShipObservation {
+                    velocity: (other_vx - vx, other_vy - vy),
+                    direction: other.direction,
+                };
+                (hit, Some(other.id), Some(ship))
+            }
+        };
+        res.push(RayObservation {
+            hit,
+            angle: ray_direction,
+            relative_angle,
+            id,
+            normal: ray_hit.normal,
+            ship,
+        });
     }
+    res
 }
 
 pub trait PlayerTrait {
     fn get_id(&self) -> usize;
+    /// Team of the player, it's the player's id unless the team is set
+    fn get_team(&self) -> usize;
+    fn set_team(&mut self, team: usize);
     fn get_x(&self) -> f64;
     fn get_y(&self) -> f64;
     fn get_direction(&self) -> f64;
     fn rotate(&mut self, angle: f64);
     fn get_speed(&self) -> f64;
+    /// Sets the speed in the arcade flight model, the Newtonian one ignores it
     fn set_speed(&mut self, speed: f64);
-    fn fire(&self);
+    fn get_velocity(&self) -> (f64, f64);
+    /// Sets the thrust in `[-1; 1]` for the Newtonian flight model
+    fn set_thrust(&mut self, thrust: f64);
+    /// Sets the turn in `[-1; 1]` for the Newtonian flight model
+    fn set_turn(&mut self, turn: f64);
+    /// Launches a missile if the weapon is ready
+    fn fire(&mut self) -> Result<(), FireError>;
 }
 
 pub trait ViewTrait {
     fn view(&self) -> Vec<ViewHit>;
+    /// Same rays as `view` with what exactly they hit
+    fn view_extended(&self) -> Vec<RayObservation>;
+    /// State of the player, it's not set if the player isn't registered in a game
+    fn self_state(&self) -> Option<SelfState>;
+    /// Ships and missiles around the player, it's empty if the game has no radar
+    fn radar(&self) -> Vec<RadarContact>;
 }
 
 impl PlayerTrait for Player {
@@ -133,6 +492,14 @@ impl PlayerTrait for Player {
         self.id
     }
 
+    fn get_team(&self) -> usize {
+        self.team.unwrap_or(self.id)
+    }
+
+    fn set_team(&mut self, team: usize) {
+        self.team = Some(team);
+    }
+
     fn get_x(&self) -> f64 {
         self.x
     }
@@ -161,21 +528,29 @@ impl PlayerTrait for Player {
         }
     }
 
-    fn fire(&self) {
-        let weak_missiles = self.missiles.upgrade();
-        if weak_missiles.is_none() {
-            return;
+    fn get_velocity(&self) -> (f64, f64) {
+        (self.vx, self.vy)
+    }
+
+    fn set_thrust(&mut self, thrust: f64) {
+        self.thrust = thrust.clamp(-1.0, 1.0);
+    }
+
+    fn set_turn(&mut self, turn: f64) {
+        self.turn = turn.clamp(-1.0, 1.0);
+    }
+
+    fn fire(&mut self) -> Result<(), FireError> {
+        let (Some(mutex_missiles), Some(ids)) = (self.missiles.upgrade(), self.ids.upgrade())
+        else {
+            return Err(FireError::Inactive);
+        };
+        let (missile, event) = self.shoot(&ids)?;
+        mutex_missiles.lock().unwrap().push(missile);
+        if let Some(events) = self.events.upgrade() {
+            events.lock().unwrap().push(event);
         }
-        let mutex_missiles = weak_missiles.unwrap();
-        let mut missiles = mutex_missiles.lock().unwrap();
-        missiles.push(Missile {
-            x: self.x,
-            y: self.y,
-            direction: self.direction,
-            id: get_id(),
-            player_id: self.id,
-            speed: self.missile_speed,
-        })
+        Ok(())
     }
 }
 
@@ -184,6 +559,14 @@ impl PlayerTrait for Arc<Mutex<Player>> {
         self.lock().unwrap().get_id()
     }
 
+    fn get_team(&self) -> usize {
+        self.lock().unwrap().get_team()
+    }
+
+    fn set_team(&mut self, team: usize) {
+        self.lock().unwrap().set_team(team);
+    }
+
     fn get_x(&self) -> f64 {
         self.lock().unwrap().get_x()
     }
@@ -208,66 +591,114 @@ impl PlayerTrait for Arc<Mutex<Player>> {
         self.lock().unwrap().set_speed(speed);
     }
 
-    fn fire(&self) {
-        self.lock().unwrap().fire();
+    fn get_velocity(&self) -> (f64, f64) {
+        self.lock().unwrap().get_velocity()
+    }
+
+    fn set_thrust(&mut self, thrust: f64) {
+        self.lock().unwrap().set_thrust(thrust);
+    }
+
+    fn set_turn(&mut self, turn: f64) {
+        self.lock().unwrap().set_turn(turn);
+    }
+
+    fn fire(&mut self) -> Result<(), FireError> {
+        self.lock().unwrap().fire()
     }
 }
 
 impl ViewTrait for Arc<Mutex<Player>> {
     fn view(&self) -> Vec<ViewHit> {
-        let player = self.lock().unwrap();
+        self.view_extended().into_iter().map(|o| o.hit).collect()
+    }
 
-        let weak_game = player.game.upgrade();
-        if weak_game.is_none() {
+    fn view_extended(&self) -> Vec<RayObservation> {
+        let Some(game) = self.lock().unwrap().game.upgrade() else {
             return Vec::new();
-        }
-        let game = weak_game.unwrap();
+        };
 
-        // Get player's data
+        // Players are locked once in the registration order, so rays don't lock them
 
-        let player_direction = player.direction;
-        let player_radius = player.r;
-        let player_x = player.x;
-        let player_y = player.y;
-        let player_rays_amount = player.rays_amount;
-        let player_view_angle = player.view_angle;
-        let player_id = player.id;
-        drop(player);
+        let game = game.lock().unwrap();
+        let locked_players: Vec<_> = game
+            .players
+            .iter()
+            .map(|p| (p, p.lock().unwrap()))
+            .collect();
+        let Some((_, player)) = locked_players.iter().find(|(p, _)| Arc::ptr_eq(p, self)) else {
+            return Vec::new();
+        };
+        let others: Vec<&Player> = locked_players
+            .iter()
+            .filter(|(p, _)| !Arc::ptr_eq(p, self))
+            .map(|(_, p)| &**p)
+            .collect();
+        let missiles = game.missiles.lock().unwrap();
+        let mut view = observe(
+            player,
+            &others,
+            &missiles,
+            &game.map,
+            &game.barriers_index,
+            &game.config,
+        );
+        if let Some(noise) = player.sensor_noise.or(game.config.sensor_noise) {
+            add_noise(
+                &mut view,
+                &noise,
+                noise_seed(game.seed, player.id, game.time),
+            );
+        }
+        view
+    }
 
-        // Send rays and aggregate hits
+    fn self_state(&self) -> Option<SelfState> {
+        let game = self.lock().unwrap().game.upgrade()?;
+        let game = game.lock().unwrap();
+        let state = self.lock().unwrap().self_state(&game.map, game.time);
+        Some(state)
+    }
 
-        let mut res = Vec::new();
-        for i in 0..player_rays_amount {
-            let angle_offset = if player_rays_amount > 1 {
-                player_view_angle / ((player_rays_amount - 1) as f64)
-            } else {
-                player_view_angle
-            };
-            let norm_i: f64 = (i as i16 - (player_rays_amount as i16 / 2)) as f64; // Example: if N_RAYS = 7 and i is [0;7), then norm_i will be -[3;3].
-            let ray_direction = player_direction + norm_i * angle_offset;
-            let ray_hit = ray_marching(&game, player_x, player_y, ray_direction, player_id);
-
-            match ray_hit {
-                RayHit::Barrier(x, y) => res.push(ViewHit::Barrier(
-                    ((player_x - x).powi(2) + (player_y - y).powi(2)).sqrt() - player_radius,
-                )),
-                RayHit::Border(x, y) => res.push(ViewHit::Border(
-                    ((player_x - x).powi(2) + (player_y - y).powi(2)).sqrt() - player_radius,
-                )),
-                RayHit::Player(x, y) => res.push(ViewHit::Enemy(
-                    ((player_x - x).powi(2) + (player_y - y).powi(2)).sqrt() - player_radius,
-                )),
-            }
-        }
-        res
+    fn radar(&self) -> Vec<RadarContact> {
+        let Some(game) = self.lock().unwrap().game.upgrade() else {
+            return Vec::new();
+        };
+
+        let game = game.lock().unwrap();
+        let locked_players: Vec<_> = game
+            .players
+            .iter()
+            .map(|p| (p, p.lock().unwrap()))
+            .collect();
+        let Some((_, player)) = locked_players.iter().find(|(p, _)| Arc::ptr_eq(p, self)) else {
+            return Vec::new();
+        };
+        let others: Vec<&Player> = locked_players
+            .iter()
+            .filter(|(p, _)| !Arc::ptr_eq(p, self))
+            .map(|(_, p)| &**p)
+            .collect();
+        let missiles = game.missiles.lock().unwrap();
+        scan(
+            player,
+            &others,
+            &missiles,
+            &game.map,
+            &game.barriers_index,
+            &game.config,
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        game::{Game, RegisterPlayer},
+        config::GameConfig,
+        game::{Game, GameTrait, RegisterPlayer},
         map::{Barrier, Map},
+        sensors::SensorNoise,
+        weapon::{FireError, WeaponConfig},
     };
 
     use super::{Player, PlayerTrait, ViewHit, ViewTrait};
@@ -316,12 +747,8 @@ mod tests {
     #[test]
     fn test_view() {
         let mut map = Map::new_without_seed(100.0, 100.0, 0, 0.0);
-        map.barriers.push(Barrier {
-            x: 50.0,
-            y: 100.0,
-            r: 10.0,
-        });
-        let game = Game::new(map);
+        map.barriers.push(Barrier::circle(50.0, 100.0, 10.0));
+        let game = Game::new(map, GameConfig::default());
         let mut p = Player::new_with_direction(
             50.0,
             50.0,
@@ -343,14 +770,196 @@ mod tests {
         assert_eq!(p.view().first().unwrap(), &ViewHit::Border(40.0));
     }
 
+    #[test]
+    fn test_view_extended() {
+        let mut map = Map::new_without_seed(100.0, 100.0, 0, 0.0);
+        map.barriers.push(Barrier::circle(50.0, 100.0, 10.0));
+        let game = Game::new(map, GameConfig::default());
+        let mut p = Player::new_with_direction(
+            50.0,
+            50.0,
+            10.0,
+            MAX_SPEED,
+            VIEW_ANGLE,
+            1,
+            90.0,
+            MISSILE_SPEED,
+        );
+        let mut p2 =
+            Player::new_with_direction(100.0, 50.0, 10.0, 10.0, VIEW_ANGLE, 0, 0.0, MISSILE_SPEED);
+        game.register_player(&p);
+        game.register_player(&p2);
+        p2.set_speed(5.0);
+
+        let enemy = p.view_extended()[0].clone();
+        assert_eq!(enemy.hit, ViewHit::Enemy(30.0));
+        assert_eq!((enemy.angle, enemy.relative_angle), (90.0, 0.0));
+        assert_eq!(enemy.id, Some(p2.get_id()));
+        assert_eq!(enemy.normal, (-1.0, 0.0));
+        let ship = enemy.ship.unwrap();
+        assert_eq!(ship.direction, 0.0);
+        assert!(ship.velocity.0.abs() < 1e-9 && (ship.velocity.1 - 5.0).abs() < 1e-9);
+
+        p.rotate(-90.0);
+        let barrier = p.view_extended()[0].clone();
+        assert_eq!(barrier.hit, ViewHit::Barrier(30.0));
+        assert_eq!(barrier.id, Some(0));
+        assert_eq!(barrier.normal, (0.0, -1.0));
+        assert_eq!(barrier.ship, None);
+    }
+
+    #[test]
+    fn test_view_missiles() {
+        for missiles_visible in [false, true] {
+            let config = GameConfig {
+                missiles_visible,
+                ..Default::default()
+            };
+            let game = Game::new(Map::new_without_seed(100.0, 100.0, 0, 0.0), config);
+            let p = Player::new_with_direction(
+                50.0,
+                10.0,
+                R,
+                MAX_SPEED,
+                VIEW_ANGLE,
+                1,
+                0.0,
+                MISSILE_SPEED,
+            );
+            let mut enemy = Player::new_with_direction(
+                50.0,
+                90.0,
+                R,
+                MAX_SPEED,
+                VIEW_ANGLE,
+                1,
+                180.0,
+                MISSILE_SPEED,
+            );
+            game.register_player(&p);
+            game.register_player(&enemy);
+            enemy.fire().unwrap();
+
+            let expected = if missiles_visible {
+                ViewHit::Missile {
+                    distance: 77.0,
+                    player_id: enemy.get_id(),
+                    direction: 180.0,
+                }
+            } else {
+                ViewHit::Enemy(78.0)
+            };
+            assert_eq!(p.view(), vec![expected]);
+            // Own missiles are never seen
+            assert_eq!(enemy.view(), vec![ViewHit::Enemy(78.0)]);
+        }
+    }
+
+    #[test]
+    fn test_self_state() {
+        let map = Map::new_without_seed(100.0, 200.0, 0, 0.0);
+        let config = GameConfig {
+            weapon: WeaponConfig {
+                cooldown: 0.5,
+                ammo: Some(2),
+                energy: None,
+            },
+            ..Default::default()
+        };
+        let mut game = Game::new(map, config);
+        let mut p = get_player();
+        let stub_p = Player::new_with_direction(
+            90.0,
+            90.0,
+            R,
+            MAX_SPEED,
+            VIEW_ANGLE,
+            RAYS_AMOUNT,
+            DIRECTION,
+            MISSILE_SPEED,
+        );
+        assert_eq!(p.self_state(), None);
+
+        game.register_player(&p);
+        game.register_player(&stub_p);
+        p.fire().unwrap();
+        game.process(0.2);
+
+        let state = p.self_state().unwrap();
+        assert_eq!((state.x, state.y), (X, Y));
+        assert_eq!((state.normalized_x, state.normalized_y), (0.5, 0.25));
+        assert_eq!((state.direction, state.speed), (DIRECTION, 0.0));
+        assert!((state.cooldown - 0.3).abs() < 1e-9);
+        assert_eq!(state.ammo, Some(1));
+        assert_eq!(state.hp, 1.0);
+        assert!((state.time - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sensor_noise() {
+        let config = GameConfig {
+            sensor_noise: Some(SensorNoise {
+                dropout: 1.0,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let game = Game::new(Map::new_without_seed(100.0, 100.0, 0, 0.0), config);
+        let p = Player::new_with_direction(
+            50.0,
+            50.0,
+            10.0,
+            MAX_SPEED,
+            VIEW_ANGLE,
+            1,
+            DIRECTION,
+            MISSILE_SPEED,
+        );
+        game.register_player(&p);
+        assert_eq!(p.view(), vec![ViewHit::Unknown]);
+
+        // The player's own noise overrides the game's one
+        p.lock().unwrap().sensor_noise = Some(SensorNoise::default());
+        assert_eq!(p.view(), vec![ViewHit::Border(40.0)]);
+    }
+
+    #[test]
+    fn test_view_ally() {
+        let game = Game::new(
+            Map::new_without_seed(100.0, 100.0, 0, 0.0),
+            GameConfig::default(),
+        );
+        let mut p = Player::new_with_direction(
+            50.0,
+            50.0,
+            10.0,
+            MAX_SPEED,
+            VIEW_ANGLE,
+            1,
+            90.0,
+            MISSILE_SPEED,
+        );
+        let mut ally = Player::new(100.0, 50.0, 10.0, MAX_SPEED, VIEW_ANGLE, 0, MISSILE_SPEED);
+        let enemy = Player::new(0.0, 50.0, 10.0, MAX_SPEED, VIEW_ANGLE, 0, MISSILE_SPEED);
+        p.set_team(1);
+        ally.set_team(1);
+        game.register_player(&p);
+        game.register_player(&ally);
+        game.register_player(&enemy);
+
+        assert_eq!(p.view().first().unwrap(), &ViewHit::Ally(30.0));
+        p.rotate(180.0);
+        assert_eq!(p.view().first().unwrap(), &ViewHit::Enemy(30.0));
+    }
+
     #[test]
     fn test_fire() {
         let map = Map::new_without_seed(100.0, 100.0, 0, 0.0);
-        let game = Game::new(map);
-        let mutex_player = get_player();
+        let game = Game::new(map, GameConfig::default());
+        let mut mutex_player = get_player();
         game.register_player(&mutex_player);
 
-        mutex_player.fire();
+        mutex_player.fire().unwrap();
 
         let locked_game = game.lock().unwrap();
         let locked_missiles = locked_game.missiles.lock().unwrap();
@@ -362,4 +971,41 @@ mod tests {
         assert_eq!(missile.x, player.x);
         assert_eq!(missile.y, player.y);
     }
+
+    #[test]
+    fn test_fire_limits() {
+        let map = Map::new_without_seed(100.0, 100.0, 0, 0.0);
+        let config = GameConfig {
+            weapon: WeaponConfig {
+                cooldown: 0.5,
+                ammo: Some(2),
+                energy: None,
+            },
+            ..Default::default()
+        };
+        let mut game = Game::new(map, config);
+        let mut p = get_player();
+        let stub_p = Player::new_with_direction(
+            90.0,
+            90.0,
+            R,
+            MAX_SPEED,
+            VIEW_ANGLE,
+            RAYS_AMOUNT,
+            DIRECTION,
+            MISSILE_SPEED,
+        );
+
+        assert_eq!(p.fire(), Err(FireError::Inactive));
+
+        game.register_player(&p);
+        game.register_player(&stub_p);
+
+        assert_eq!(p.fire(), Ok(()));
+        assert_eq!(p.fire(), Err(FireError::Cooldown));
+        game.process(0.5);
+        assert_eq!(p.fire(), Ok(()));
+        game.process(0.5);
+        assert_eq!(p.fire(), Err(FireError::NoAmmo));
+    }
 }
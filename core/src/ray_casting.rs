@@ -0,0 +1,342 @@
+use super::geometry::{ray_circle_intersection, ray_rect_exit, Circle};
+use super::map::Map;
+use super::ray_marching::ray_marching;
+use super::spatial::SpatialGrid;
+
+/// Algorithm used to find what a ray hits
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RayCastBackend {
+    /// Exact ray-vs-circle and ray-vs-border intersection
+    Analytic,
+    /// Sphere tracing, the hit point is approximate
+    SphereTracing,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RayHitKind {
+    Border,
+    /// Index of the barrier in `map.barriers`
+    Barrier(usize),
+    /// Index of the player in the slice passed to the cast
+    Player(usize),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RayHit {
+    pub kind: RayHitKind,
+    pub x: f64,
+    pub y: f64,
+    /// Distance from the ray's origin to the hit point
+    pub distance: f64,
+    /// Unit normal of the hit surface, it points toward the ray's origin
+    pub normal: (f64, f64),
+}
+
+/// Sends a ray from `(x, y)` in `direction` degrees. `barriers_index` is the index of
+/// `map.barriers`, `players` are the alive players and other moving objects the ray can hit,
+/// the ray's owner must not be there.
+pub fn cast_ray(
+    map: &Map,
+    barriers_index: &SpatialGrid,
+    players: &[Circle],
+    x: f64,
+    y: f64,
+    direction: f64,
+    backend: RayCastBackend,
+) -> RayHit {
+    match backend {
+        RayCastBackend::Analytic => ray_casting(map, barriers_index, players, x, y, direction),
+        RayCastBackend::SphereTracing => ray_marching(map, players, x, y, direction),
+    }
+}
+
+pub fn ray_casting(
+    map: &Map,
+    barriers_index: &SpatialGrid,
+    players: &[Circle],
+    x: f64,
+    y: f64,
+    direction: f64,
+) -> RayHit {
+    let dx = (direction * std::f64::consts::PI / 180.0).sin();
+    let dy = (direction * std::f64::consts::PI / 180.0).cos();
+
+    // Borders are always hit, so they are the initial candidate
+
+    let (distance, normal) = ray_rect_exit(x, y, dx, dy, map.width, map.height);
+    let mut kind = RayHitKind::Border;
+    let mut min_distance = distance;
+
+    // Barriers can only be closer than borders, cells are walked until a hit inside the walked
+    // cells is found
+
+    barriers_index.traverse_ray(x, y, dx, dy, min_distance, |indices, t_exit| {
+        for &i in indices {
+            if let Some(distance) = map.barriers[i].ray_intersection(x, y, dx, dy) {
+                if distance < min_distance {
+                    min_distance = distance;
+                    kind = RayHitKind::Barrier(i);
+                }
+            }
+        }
+        min_distance <= t_exit
+    });
+
+    // Players
+
+    for (i, circle) in players.iter().enumerate() {
+        if let Some(distance) = ray_circle_intersection(x, y, dx, dy, circle) {
+            if distance < min_distance {
+                min_distance = distance;
+                kind = RayHitKind::Player(i);
+            }
+        }
+    }
+
+    let hit_x = x + dx * min_distance;
+    let hit_y = y + dy * min_distance;
+    let normal = match kind {
+        RayHitKind::Border => normal,
+        RayHitKind::Barrier(i) => map.barriers[i].normal(hit_x, hit_y, dx, dy),
+        RayHitKind::Player(i) => circle_normal(&players[i], hit_x, hit_y, dx, dy),
+    };
+    RayHit {
+        kind,
+        x: hit_x,
+        y: hit_y,
+        distance: min_distance,
+        normal,
+    }
+}
+
+/// Unit normal of the circle at the point, the ray's direction is used if the point is the center
+pub(crate) fn circle_normal(circle: &Circle, x: f64, y: f64, dx: f64, dy: f64) -> (f64, f64) {
+    let (nx, ny) = (x - circle.x, y - circle.y);
+    let length = (nx * nx + ny * ny).sqrt();
+    if length > 0.0 {
+        (nx / length, ny / length)
+    } else {
+        (-dx, -dy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::{ray_circle_intersection, Circle};
+    use crate::map::{Barrier, Map};
+    use crate::shape::Shape;
+
+    use super::{cast_ray, RayCastBackend, RayHitKind};
+
+    fn get_map() -> Map {
+        let mut map = Map::new(100.0, 100.0, 0, 0.0, 0);
+        map.barriers.push(Barrier::circle(50.0, 80.0, 10.0));
+        map
+    }
+
+    #[test]
+    fn test_barrier_hit() {
+        let map = get_map();
+        let hit = cast_ray(
+            &map,
+            &map.build_index(),
+            &[],
+            50.0,
+            20.0,
+            0.0,
+            RayCastBackend::Analytic,
+        );
+        assert_eq!(hit.kind, RayHitKind::Barrier(0));
+        assert_eq!(hit.distance, 50.0);
+        assert_eq!((hit.x, hit.y), (50.0, 70.0));
+        assert_eq!(hit.normal, (0.0, -1.0));
+    }
+
+    #[test]
+    fn test_grazing_barrier_hit() {
+        let map = get_map();
+        // The ray passes 9.99 units from the barrier's center
+        let hit = cast_ray(
+            &map,
+            &map.build_index(),
+            &[],
+            40.01,
+            20.0,
+            0.0,
+            RayCastBackend::Analytic,
+        );
+        assert_eq!(hit.kind, RayHitKind::Barrier(0));
+        let expected = 60.0 - (10.0f64.powi(2) - 9.99f64.powi(2)).sqrt();
+        assert!((hit.distance - expected).abs() < 1e-9);
+        assert!(hit.normal.0 < 0.0 && hit.normal.1 < 0.0);
+    }
+
+    #[test]
+    fn test_player_hit() {
+        let map = get_map();
+        let players = [Circle {
+            x: 50.0,
+            y: 40.0,
+            r: 5.0,
+        }];
+        let hit = cast_ray(
+            &map,
+            &map.build_index(),
+            &players,
+            50.0,
+            20.0,
+            0.0,
+            RayCastBackend::Analytic,
+        );
+        assert_eq!(hit.kind, RayHitKind::Player(0));
+        assert_eq!(hit.distance, 15.0);
+    }
+
+    #[test]
+    fn test_border_hit() {
+        let map = get_map();
+        let hit = cast_ray(
+            &map,
+            &map.build_index(),
+            &[],
+            50.0,
+            20.0,
+            -90.0,
+            RayCastBackend::Analytic,
+        );
+        assert_eq!(hit.kind, RayHitKind::Border);
+        assert_eq!(hit.distance, 50.0);
+        assert_eq!(hit.normal, (1.0, 0.0));
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        let map = Map::new(960.0, 540.0, 30, 40.0, 12345);
+        let index = map.build_index();
+        for i in 0..36 {
+            let direction = i as f64 * 10.0;
+            let analytic = cast_ray(
+                &map,
+                &index,
+                &[],
+                480.0,
+                270.0,
+                direction,
+                RayCastBackend::Analytic,
+            );
+            let tracing = cast_ray(
+                &map,
+                &index,
+                &[],
+                480.0,
+                270.0,
+                direction,
+                RayCastBackend::SphereTracing,
+            );
+            assert_eq!(analytic.kind, tracing.kind);
+            // Sphere tracing stops a bit before the surface, more for grazing rays
+            assert!(tracing.distance <= analytic.distance + 1e-9);
+            assert!(analytic.distance - tracing.distance < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_shaped_backends_agree() {
+        let mut map = Map::new(960.0, 540.0, 0, 0.0, 12345);
+        let shapes = [
+            Shape::Rect {
+                width: 300.0,
+                height: 20.0,
+                angle: 0.0,
+            },
+            Shape::Rect {
+                width: 60.0,
+                height: 30.0,
+                angle: 30.0,
+            },
+            Shape::Capsule {
+                length: 80.0,
+                r: 10.0,
+                angle: 45.0,
+            },
+            Shape::Polygon {
+                points: vec![(-30.0, -20.0), (40.0, -10.0), (10.0, 35.0)],
+            },
+        ];
+        let centers = [
+            (480.0, 400.0),
+            (650.0, 200.0),
+            (300.0, 150.0),
+            (480.0, 120.0),
+        ];
+        for (shape, (x, y)) in shapes.into_iter().zip(centers) {
+            map.barriers.push(Barrier { x, y, shape });
+        }
+        let index = map.build_index();
+
+        let mut barrier_hits = 0;
+        for i in 0..72 {
+            let direction = i as f64 * 5.0 + 2.5;
+            let analytic = cast_ray(
+                &map,
+                &index,
+                &[],
+                480.0,
+                270.0,
+                direction,
+                RayCastBackend::Analytic,
+            );
+            let tracing = cast_ray(
+                &map,
+                &index,
+                &[],
+                480.0,
+                270.0,
+                direction,
+                RayCastBackend::SphereTracing,
+            );
+            assert_eq!(analytic.kind, tracing.kind);
+            assert!(tracing.distance <= analytic.distance + 1e-9);
+            assert!(analytic.distance - tracing.distance < 0.1);
+            if let RayHitKind::Barrier(_) = analytic.kind {
+                barrier_hits += 1;
+                let (nx, ny) = analytic.normal;
+                assert!((nx.hypot(ny) - 1.0).abs() < 1e-9);
+                // The normal faces the ray
+                let (dx, dy) = (direction.to_radians().sin(), direction.to_radians().cos());
+                assert!(nx * dx + ny * dy < 0.0);
+            }
+        }
+        assert!(barrier_hits > 0);
+    }
+
+    #[test]
+    fn test_index_matches_linear_scan() {
+        let map = Map::new(2000.0, 2000.0, 3000, 15.0, 12345);
+        let index = map.build_index();
+        for i in 0..72 {
+            let direction = i as f64 * 5.0;
+            let dx = (direction * std::f64::consts::PI / 180.0).sin();
+            let dy = (direction * std::f64::consts::PI / 180.0).cos();
+            let hit = cast_ray(
+                &map,
+                &index,
+                &[],
+                1000.0,
+                1000.0,
+                direction,
+                RayCastBackend::Analytic,
+            );
+
+            let expected = map
+                .barriers
+                .iter()
+                .filter_map(|b| ray_circle_intersection(1000.0, 1000.0, dx, dy, &b.into()))
+                .fold(f64::INFINITY, f64::min);
+            if expected.is_finite() {
+                assert!(matches!(hit.kind, RayHitKind::Barrier(_)));
+                assert_eq!(hit.distance, expected);
+            }
+        }
+    }
+}
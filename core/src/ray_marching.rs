@@ -1,28 +1,17 @@
-use std::sync::{Arc, Mutex};
-
-use crate::player::PlayerStatus;
-
-use super::game::Game;
+use super::geometry::Circle;
+use super::map::Map;
+use super::ray_casting::{circle_normal, RayHit, RayHitKind};
 
 const DISTANCE_LIMIT: f64 = 0.01;
 
-pub enum RayHit {
-    Border(f64, f64),
-    Barrier(f64, f64),
-    Player(f64, f64),
-}
-
-pub fn ray_marching(
-    game: &Arc<Mutex<Game>>,
-    x: f64,
-    y: f64,
-    direction: f64,
-    player_id: usize,
-) -> RayHit {
-    let game = game.lock().unwrap();
-    let map_x = game.map.width / 2.0;
-    let map_y = game.map.height / 2.0;
+/// Sphere tracing version of `ray_casting`, the hit point is approximate
+pub fn ray_marching(map: &Map, players: &[Circle], x: f64, y: f64, direction: f64) -> RayHit {
+    let map_x = map.width / 2.0;
+    let map_y = map.height / 2.0;
+    let dx = (direction * std::f64::consts::PI / 180.0).sin();
+    let dy = (direction * std::f64::consts::PI / 180.0).cos();
 
+    let mut distance = 0.0;
     let mut next_x = x;
     let mut next_y = y;
 
@@ -38,45 +27,69 @@ pub fn ray_marching(
         };
 
         if min_distance <= DISTANCE_LIMIT {
-            break RayHit::Border(next_x, next_y);
+            let normal = if border_dx < border_dy {
+                (-(next_x - map_x).signum(), 0.0)
+            } else {
+                (0.0, -(next_y - map_y).signum())
+            };
+            break RayHit {
+                kind: RayHitKind::Border,
+                x: next_x,
+                y: next_y,
+                distance,
+                normal,
+            };
         }
 
         // Find the min distance to barriers and check the limit
 
-        for barrier in game.map.barriers.iter() {
-            let barrier_distance =
-                ((next_x - barrier.x).powi(2) + (next_y - barrier.y).powi(2)).sqrt() - barrier.r;
+        let mut nearest_barrier = 0;
+        for (i, barrier) in map.barriers.iter().enumerate() {
+            let barrier_distance = barrier.distance(next_x, next_y);
             if barrier_distance < min_distance {
                 min_distance = barrier_distance;
+                nearest_barrier = i;
             }
         }
 
         if min_distance <= DISTANCE_LIMIT {
-            break RayHit::Barrier(next_x, next_y);
+            break RayHit {
+                kind: RayHitKind::Barrier(nearest_barrier),
+                x: next_x,
+                y: next_y,
+                distance,
+                normal: map.barriers[nearest_barrier].normal(next_x, next_y, dx, dy),
+            };
         }
 
         // Find the min distance to players and check the limit
 
-        for player in game.players.iter() {
-            let player = player.lock().unwrap();
-            if player.id == player_id || player.status != PlayerStatus::InGame {
-                continue;
-            }
-
+        let mut nearest: Option<Circle> = None;
+        let mut nearest_player = 0;
+        for (i, player) in players.iter().enumerate() {
             let player_distance =
                 ((next_x - player.x).powi(2) + (next_y - player.y).powi(2)).sqrt() - player.r;
             if player_distance < min_distance {
                 min_distance = player_distance;
+                nearest = Some(*player);
+                nearest_player = i;
             }
         }
 
         if min_distance <= DISTANCE_LIMIT {
-            break RayHit::Player(next_x, next_y);
+            break RayHit {
+                kind: RayHitKind::Player(nearest_player),
+                x: next_x,
+                y: next_y,
+                distance,
+                normal: circle_normal(&nearest.unwrap(), next_x, next_y, dx, dy),
+            };
         }
 
         // Update for the next iteration
 
-        next_x += (direction * std::f64::consts::PI / 180.0).sin() * min_distance;
-        next_y += (direction * std::f64::consts::PI / 180.0).cos() * min_distance;
+        distance += min_distance;
+        next_x = x + dx * distance;
+        next_y = y + dy * distance;
     }
 }
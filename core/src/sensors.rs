@@ -0,0 +1,432 @@
+use std::f64::consts::PI;
+use std::mem::discriminant;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::config::GameConfig;
+use super::geometry::Circle;
+use super::map::Map;
+use super::player::{Missile, Player, PlayerStatus, PlayerTrait, RayObservation, ViewHit};
+use super::ray_casting::{cast_ray, RayHitKind};
+use super::spatial::SpatialGrid;
+
+/// Imperfections of the rays of players, the default one is a perfect sensor
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SensorNoise {
+    /// Standard deviation of the Gaussian noise added to distances
+    pub distance_std: f64,
+    /// Probability that a ray's reading is lost
+    pub dropout: f64,
+    /// Probability that a ray reports a wrong kind of object
+    pub misclassification: f64,
+}
+
+/// Settings of the radar, a sensor that sees all around the player
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RadarConfig {
+    /// Max distance between the centers of the player and a contact
+    pub radius: f64,
+    /// Whether contacts report ids of ships and missiles
+    pub show_ids: bool,
+    /// Whether barriers hide what's behind them
+    pub line_of_sight: bool,
+}
+
+/// What a radar contact is
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RadarTarget {
+    Enemy,
+    Ally,
+    Missile,
+}
+
+/// A ship or a missile seen by the radar
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RadarContact {
+    pub target: RadarTarget,
+    /// Direction to the contact in degrees
+    pub bearing: f64,
+    /// Direction to the contact relative to the player's direction, it's in `(-180; 180]`
+    pub relative_bearing: f64,
+    /// Distance between the centers of the player and the contact
+    pub distance: f64,
+    /// Id of the ship or the missile, it's set only if `show_ids` is enabled
+    pub id: Option<usize>,
+}
+
+/// Finds the alive ships and the missiles of other players within the radar's radius
+pub(crate) fn scan(
+    player: &Player,
+    others: &[&Player],
+    missiles: &[Missile],
+    map: &Map,
+    barriers_index: &SpatialGrid,
+    config: &GameConfig,
+) -> Vec<RadarContact> {
+    let Some(radar) = config.radar else {
+        return Vec::new();
+    };
+    let team = player.get_team();
+    let ships = others
+        .iter()
+        .filter(|p| p.status == PlayerStatus::InGame)
+        .map(|p| {
+            let target = if p.get_team() == team {
+                RadarTarget::Ally
+            } else {
+                RadarTarget::Enemy
+            };
+            (target, p.circle(), p.id)
+        });
+    let missiles = missiles
+        .iter()
+        .filter(|m| m.player_id != player.id)
+        .map(|m| {
+            let circle = Circle {
+                x: m.x,
+                y: m.y,
+                r: config.missile_radius,
+            };
+            (RadarTarget::Missile, circle, m.id)
+        });
+
+    let mut res = Vec::new();
+    for (target, circle, id) in ships.chain(missiles) {
+        let (dx, dy) = (circle.x - player.x, circle.y - player.y);
+        let distance = dx.hypot(dy);
+        if distance > radar.radius {
+            continue;
+        }
+        // Same angles as directions, 0 is along the y axis
+        let bearing = dx.atan2(dy).to_degrees().rem_euclid(360.0);
+        if radar.line_of_sight && distance > circle.r {
+            let hit = cast_ray(
+                map,
+                barriers_index,
+                &[],
+                player.x,
+                player.y,
+                bearing,
+                config.ray_cast_backend,
+            );
+            if matches!(hit.kind, RayHitKind::Barrier(_)) && hit.distance < distance - circle.r {
+                continue;
+            }
+        }
+        let mut relative_bearing = (bearing - player.direction).rem_euclid(360.0);
+        if relative_bearing > 180.0 {
+            relative_bearing -= 360.0;
+        }
+        res.push(RadarContact {
+            target,
+            bearing,
+            relative_bearing,
+            distance,
+            id: radar.show_ids.then_some(id),
+        });
+    }
+    res
+}
+
+/// Seed of the noise of a player's view at the game time. The same moment is always seen the
+/// same way, so repeated views don't average the noise out.
+pub(crate) fn noise_seed(game_seed: u64, player_id: usize, time: f64) -> u64 {
+    game_seed ^ (player_id as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ time.to_bits()
+}
+
+/// Applies the noise to the rays of a view
+pub(crate) fn add_noise(observations: &mut [RayObservation], noise: &SensorNoise, seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for observation in observations.iter_mut() {
+        // Every ray draws the same amount of numbers, so rays don't affect each other
+        let dropped = rng.gen_bool(noise.dropout.clamp(0.0, 1.0));
+        let misclassified = rng.gen_bool(noise.misclassification.clamp(0.0, 1.0));
+        // Uniform for both 3 and 4 wrong kinds
+        let kind = rng.gen_range(0..12);
+        let error = noise.distance_std * standard_normal(&mut rng);
+
+        let Some(distance) = distance(&observation.hit) else {
+            continue;
+        };
+        let distance = (distance + error).max(0.0);
+        observation.hit = if dropped {
+            ViewHit::Unknown
+        } else if misclassified {
+            // Any kind but the true one
+            let kinds: Vec<ViewHit> = [
+                ViewHit::Border(distance),
+                ViewHit::Barrier(distance),
+                ViewHit::Enemy(distance),
+                ViewHit::Ally(distance),
+            ]
+            .into_iter()
+            .filter(|k| discriminant(k) != discriminant(&observation.hit))
+            .collect();
+            kinds[kind % kinds.len()]
+        } else {
+            with_distance(observation.hit, distance)
+        };
+        if dropped || misclassified {
+            // Nothing is known about a wrong or lost reading
+            observation.id = None;
+            observation.ship = None;
+        }
+    }
+}
+
+fn distance(hit: &ViewHit) -> Option<f64> {
+    match *hit {
+        ViewHit::Barrier(d) | ViewHit::Border(d) | ViewHit::Enemy(d) | ViewHit::Ally(d) => Some(d),
+        ViewHit::Missile { distance, .. } => Some(distance),
+        ViewHit::Unknown => None,
+    }
+}
+
+fn with_distance(hit: ViewHit, distance: f64) -> ViewHit {
+    match hit {
+        ViewHit::Barrier(_) => ViewHit::Barrier(distance),
+        ViewHit::Border(_) => ViewHit::Border(distance),
+        ViewHit::Enemy(_) => ViewHit::Enemy(distance),
+        ViewHit::Ally(_) => ViewHit::Ally(distance),
+        ViewHit::Missile {
+            player_id,
+            direction,
+            ..
+        } => ViewHit::Missile {
+            distance,
+            player_id,
+            direction,
+        },
+        ViewHit::Unknown => ViewHit::Unknown,
+    }
+}
+
+/// Box-Muller transform, rand doesn't ship a normal distribution without rand_distr
+fn standard_normal<R: Rng>(rng: &mut R) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::GameConfig,
+        map::{Barrier, Map},
+        player::{Missile, Player, RayObservation, ViewHit},
+    };
+
+    use super::{add_noise, scan, RadarConfig, RadarContact, RadarTarget, SensorNoise};
+
+    fn get_view() -> Vec<RayObservation> {
+        (0..1000)
+            .map(|i| RayObservation {
+                hit: ViewHit::Enemy(100.0),
+                angle: i as f64,
+                relative_angle: 0.0,
+                id: Some(1),
+                normal: (0.0, 1.0),
+                ship: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_perfect_sensor() {
+        let mut view = get_view();
+        add_noise(&mut view, &SensorNoise::default(), 1);
+        assert_eq!(view, get_view());
+    }
+
+    #[test]
+    fn test_noise_is_seeded() {
+        let noise = SensorNoise {
+            distance_std: 5.0,
+            dropout: 0.1,
+            misclassification: 0.1,
+        };
+        let mut first = get_view();
+        let mut second = get_view();
+        let mut third = get_view();
+        add_noise(&mut first, &noise, 1);
+        add_noise(&mut second, &noise, 1);
+        add_noise(&mut third, &noise, 2);
+        assert_eq!(first, second);
+        assert_ne!(first, third);
+    }
+
+    #[test]
+    fn test_distance_noise() {
+        let mut view = get_view();
+        add_noise(
+            &mut view,
+            &SensorNoise {
+                distance_std: 5.0,
+                ..Default::default()
+            },
+            1,
+        );
+        let errors: Vec<f64> = view
+            .iter()
+            .map(|o| match o.hit {
+                ViewHit::Enemy(d) => d - 100.0,
+                _ => panic!("the kind must not change"),
+            })
+            .collect();
+        let mean = errors.iter().sum::<f64>() / errors.len() as f64;
+        let std =
+            (errors.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / errors.len() as f64).sqrt();
+        assert!(mean.abs() < 0.5);
+        assert!((std - 5.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_dropout_and_misclassification() {
+        let mut view = get_view();
+        add_noise(
+            &mut view,
+            &SensorNoise {
+                dropout: 1.0,
+                ..Default::default()
+            },
+            1,
+        );
+        assert!(view
+            .iter()
+            .all(|o| o.hit == ViewHit::Unknown && o.id.is_none()));
+
+        let mut view = get_view();
+        add_noise(
+            &mut view,
+            &SensorNoise {
+                misclassification: 1.0,
+                ..Default::default()
+            },
+            1,
+        );
+        assert!(view.iter().all(|o| !matches!(o.hit, ViewHit::Enemy(_))));
+        for hit in [
+            ViewHit::Border(100.0),
+            ViewHit::Barrier(100.0),
+            ViewHit::Ally(100.0),
+        ] {
+            assert!(view.iter().any(|o| o.hit == hit));
+        }
+    }
+
+    fn get_radar_config(line_of_sight: bool) -> GameConfig {
+        GameConfig {
+            radar: Some(RadarConfig {
+                radius: 50.0,
+                show_ids: true,
+                line_of_sight,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn get_ship(id: usize, x: f64, y: f64, team: usize) -> Player {
+        let mut player = Player::new_plain(x, y, 1.0, 0.0, 60.0, 1, Some(90.0), 1.0);
+        player.id = id;
+        player.team = Some(team);
+        player
+    }
+
+    #[test]
+    fn test_radar() {
+        let map = Map::new(100.0, 100.0, 0, 0.0, 1);
+        let player = get_ship(0, 50.0, 50.0, 0);
+        let ally = get_ship(1, 50.0, 80.0, 0);
+        let enemy = get_ship(2, 20.0, 50.0, 2);
+        let far = get_ship(3, 95.0, 95.0, 3);
+        let missile = Missile {
+            x: 60.0,
+            y: 50.0,
+            direction: 0.0,
+            id: 5,
+            player_id: 2,
+            team: 2,
+            speed: 1.0,
+            traveled: 0.0,
+            age: 0.0,
+        };
+        let own_missile = Missile {
+            player_id: 0,
+            ..missile.clone()
+        };
+
+        let contacts = scan(
+            &player,
+            &[&ally, &enemy, &far],
+            &[missile, own_missile],
+            &map,
+            &map.build_index(),
+            &get_radar_config(false),
+        );
+        assert_eq!(
+            contacts,
+            vec![
+                RadarContact {
+                    target: RadarTarget::Ally,
+                    bearing: 0.0,
+                    relative_bearing: -90.0,
+                    distance: 30.0,
+                    id: Some(1),
+                },
+                RadarContact {
+                    target: RadarTarget::Enemy,
+                    bearing: 270.0,
+                    relative_bearing: 180.0,
+                    distance: 30.0,
+                    id: Some(2),
+                },
+                RadarContact {
+                    target: RadarTarget::Missile,
+                    bearing: 90.0,
+                    relative_bearing: 0.0,
+                    distance: 10.0,
+                    id: Some(5),
+                },
+            ]
+        );
+
+        let contacts = scan(
+            &player,
+            &[&ally],
+            &[],
+            &map,
+            &map.build_index(),
+            &GameConfig::default(),
+        );
+        assert!(contacts.is_empty());
+    }
+
+    #[test]
+    fn test_radar_line_of_sight() {
+        let mut map = Map::new(100.0, 100.0, 0, 0.0, 1);
+        map.barriers.push(Barrier::circle(50.0, 65.0, 5.0));
+        let player = get_ship(0, 50.0, 50.0, 0);
+        let enemy = get_ship(1, 50.0, 80.0, 1);
+
+        let contacts = scan(
+            &player,
+            &[&enemy],
+            &[],
+            &map,
+            &map.build_index(),
+            &get_radar_config(false),
+        );
+        assert_eq!(contacts.len(), 1);
+
+        let contacts = scan(
+            &player,
+            &[&enemy],
+            &[],
+            &map,
+            &map.build_index(),
+            &get_radar_config(true),
+        );
+        assert!(contacts.is_empty());
+    }
+}
@@ -0,0 +1,533 @@
+use super::geometry::{ray_circle_intersection, segment_circle_intersection, Circle};
+use super::map::{rotate, Barrier};
+use super::ray_casting::circle_normal;
+
+/// Outline of a barrier around its center. Angles are in degrees and rotate the shape the same
+/// way players rotate.
+///
+/// Variants are ordered for untagged deserialization: a capsule has `r` too, so it's tried before
+/// a circle.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(untagged)
+)]
+pub enum Shape {
+    /// Rectangle of `width` along the x axis and `height` along the y axis before the rotation
+    Rect {
+        width: f64,
+        height: f64,
+        #[cfg_attr(feature = "serde", serde(default))]
+        angle: f64,
+    },
+    /// Convex polygon, vertices are relative to the center
+    Polygon {
+        points: Vec<(f64, f64)>,
+    },
+    /// Segment of `length` pointing in `angle` direction and inflated by `r`
+    Capsule {
+        length: f64,
+        r: f64,
+        #[cfg_attr(feature = "serde", serde(default))]
+        angle: f64,
+    },
+    Circle {
+        r: f64,
+    },
+}
+
+impl Shape {
+    /// Whether a polygon has at least 3 vertices going around a convex outline once, in either
+    /// direction. Other shapes are always valid.
+    pub fn is_valid(&self) -> bool {
+        let Shape::Polygon { points } = self else {
+            return true;
+        };
+        if points.len() < 3 {
+            return false;
+        }
+        let n = points.len();
+        let (mut turns, mut left, mut right) = (0.0, false, false);
+        for i in 0..n {
+            let (x0, y0) = points[i];
+            let (x1, y1) = points[(i + 1) % n];
+            let (x2, y2) = points[(i + 2) % n];
+            let (ax, ay, bx, by) = (x1 - x0, y1 - y0, x2 - x1, y2 - y1);
+            let cross = ax * by - ay * bx;
+            left |= cross > 0.0;
+            right |= cross < 0.0;
+            turns += cross.atan2(ax * bx + ay * by);
+        }
+        // A star turns the same way at every vertex too, but goes around more than once
+        !(left && right) && (turns.abs() - 2.0 * std::f64::consts::PI).abs() < 1e-6
+    }
+}
+
+impl Barrier {
+    pub fn circle(x: f64, y: f64, r: f64) -> Self {
+        Barrier {
+            x,
+            y,
+            shape: Shape::Circle { r },
+        }
+    }
+
+    /// Radius of the circle around the center that covers the barrier
+    pub fn bounding_radius(&self) -> f64 {
+        if let Shape::Circle { r } = self.shape {
+            return r;
+        }
+        let outline = self.outline();
+        outline
+            .points
+            .iter()
+            .map(|&(x, y)| (x - self.x).hypot(y - self.y))
+            .fold(0.0, f64::max)
+            + outline.r
+    }
+
+    /// Distance from the point to the surface of the barrier, it's negative inside the barrier
+    pub fn distance(&self, x: f64, y: f64) -> f64 {
+        if let Shape::Circle { r } = self.shape {
+            return (x - self.x).hypot(y - self.y) - r;
+        }
+        self.outline().signed_distance(x, y).0
+    }
+
+    /// Distance between the surfaces of the barriers, it's negative if they overlap. The depth of
+    /// overlapping barriers is exact only for circles.
+    pub fn gap(&self, other: &Barrier) -> f64 {
+        if let (Shape::Circle { r: r1 }, Shape::Circle { r: r2 }) = (&self.shape, &other.shape) {
+            return (self.x - other.x).hypot(self.y - other.y) - r1 - r2;
+        }
+        let (a, b) = (self.outline(), other.outline());
+        let distance = a.core_distance(&b);
+        let intersects = distance == 0.0
+            || b.contains(a.points[0].0, a.points[0].1)
+            || a.contains(b.points[0].0, b.points[0].1);
+        let core = if intersects { -a.depth(&b) } else { distance };
+        core - a.r - b.r
+    }
+
+    /// Finds the distance along the ray with the unit direction `(dx, dy)` to the barrier like
+    /// `ray_circle_intersection`
+    pub fn ray_intersection(&self, x: f64, y: f64, dx: f64, dy: f64) -> Option<f64> {
+        if let Shape::Circle { .. } = self.shape {
+            return ray_circle_intersection(x, y, dx, dy, &Circle::from(self));
+        }
+        self.outline().ray_intersection(x, y, dx, dy)
+    }
+
+    /// Finds where the segment enters the barrier like `segment_circle_intersection`
+    pub fn segment_intersection(&self, x0: f64, y0: f64, x1: f64, y1: f64) -> Option<f64> {
+        if let Shape::Circle { r } = self.shape {
+            return segment_circle_intersection(x0, y0, x1, y1, self.x, self.y, r);
+        }
+        let outline = self.outline();
+        if outline.signed_distance(x0, y0).0 < 0.0 {
+            return Some(0.0);
+        }
+        let length = (x1 - x0).hypot(y1 - y0);
+        if length == 0.0 {
+            return None;
+        }
+        let (dx, dy) = ((x1 - x0) / length, (y1 - y0) / length);
+        outline
+            .ray_intersection(x0, y0, dx, dy)
+            .filter(|&t| t <= length)
+            .map(|t| t / length)
+    }
+
+    /// Unit normal of the surface nearest to the point, the ray's direction is used if the point
+    /// is on the skeleton of the barrier
+    pub fn normal(&self, x: f64, y: f64, dx: f64, dy: f64) -> (f64, f64) {
+        if let Shape::Circle { .. } = self.shape {
+            return circle_normal(&Circle::from(self), x, y, dx, dy);
+        }
+        match self.outline().signed_distance(x, y).1 {
+            (0.0, 0.0) => (-dx, -dy),
+            normal => normal,
+        }
+    }
+
+    fn outline(&self) -> Outline {
+        let (x, y) = (self.x, self.y);
+        let (points, r) = match self.shape {
+            Shape::Circle { r } => (vec![(x, y)], r),
+            Shape::Rect {
+                width,
+                height,
+                angle,
+            } => {
+                let (w, h) = (width / 2.0, height / 2.0);
+                let corners = [(-w, -h), (w, -h), (w, h), (-w, h)];
+                let points = corners
+                    .iter()
+                    .map(|&(cx, cy)| rotate(x + cx, y + cy, x, y, angle))
+                    .collect();
+                (points, 0.0)
+            }
+            // A polygon without points is left as its center
+            Shape::Polygon { ref points } if points.is_empty() => (vec![(x, y)], 0.0),
+            Shape::Polygon { ref points } => (
+                points.iter().map(|&(px, py)| (x + px, y + py)).collect(),
+                0.0,
+            ),
+            Shape::Capsule { length, r, angle } => {
+                let (end_x, end_y) = rotate(x, y + length / 2.0, x, y, angle);
+                (vec![(2.0 * x - end_x, 2.0 * y - end_y), (end_x, end_y)], r)
+            }
+        };
+        Outline::new(points, r)
+    }
+}
+
+/// Convex polygon inflated by `r`, every shape is one of them. A circle is a single point and
+/// a capsule is a segment.
+struct Outline {
+    points: Vec<(f64, f64)>,
+    r: f64,
+    // Sign that turns the right-hand normals of edges outward
+    orientation: f64,
+}
+
+impl Outline {
+    fn new(points: Vec<(f64, f64)>, r: f64) -> Self {
+        let area: f64 = edges(&points)
+            .map(|((x0, y0), (x1, y1))| x0 * y1 - x1 * y0)
+            .sum();
+        Outline {
+            points,
+            r,
+            orientation: if area < 0.0 { -1.0 } else { 1.0 },
+        }
+    }
+
+    fn outward_normal(&self, (x0, y0): (f64, f64), (x1, y1): (f64, f64)) -> (f64, f64) {
+        let length = (x1 - x0).hypot(y1 - y0);
+        if length == 0.0 {
+            return (0.0, 0.0);
+        }
+        (
+            (y1 - y0) / length * self.orientation,
+            -(x1 - x0) / length * self.orientation,
+        )
+    }
+
+    fn contains(&self, x: f64, y: f64) -> bool {
+        self.points.len() >= 3
+            && edges(&self.points).all(|(a, b)| {
+                let (nx, ny) = self.outward_normal(a, b);
+                (x - a.0) * nx + (y - a.1) * ny <= 0.0
+            })
+    }
+
+    /// Signed distance from the point to the surface and the outward unit normal of the nearest
+    /// surface, the normal is zero if the point is on the skeleton of a circle or a capsule
+    fn signed_distance(&self, x: f64, y: f64) -> (f64, (f64, f64)) {
+        let mut nearest = (f64::INFINITY, (0.0, 0.0), (0.0, 0.0));
+        for (a, b) in segments(&self.points) {
+            let (qx, qy) = closest_point(x, y, a, b);
+            let distance = (x - qx).hypot(y - qy);
+            if distance < nearest.0 {
+                nearest = (distance, (qx, qy), self.outward_normal(a, b));
+            }
+        }
+        let (distance, (qx, qy), edge_normal) = nearest;
+
+        if self.contains(x, y) {
+            (-distance - self.r, edge_normal)
+        } else if distance > 0.0 {
+            let normal = ((x - qx) / distance, (y - qy) / distance);
+            (distance - self.r, normal)
+        } else if self.points.len() >= 3 {
+            (-self.r, edge_normal)
+        } else {
+            (-self.r, (0.0, 0.0))
+        }
+    }
+
+    fn ray_intersection(&self, x: f64, y: f64, dx: f64, dy: f64) -> Option<f64> {
+        if self.signed_distance(x, y).0 < 0.0 {
+            return Some(0.0);
+        }
+
+        // The surface is made of the edges moved outward by `r` and the arcs around vertices
+        let mut hit: Option<f64> = None;
+        let mut update = |t: f64| {
+            hit = Some(hit.map_or(t, |h: f64| h.min(t)));
+        };
+        if self.points.len() >= 2 {
+            for (a, b) in edges(&self.points) {
+                let (nx, ny) = self.outward_normal(a, b);
+                let (ox, oy) = (nx * self.r, ny * self.r);
+                let a = (a.0 + ox, a.1 + oy);
+                let b = (b.0 + ox, b.1 + oy);
+                if let Some(t) = ray_segment_intersection(x, y, dx, dy, a, b) {
+                    update(t);
+                }
+            }
+        }
+        if self.r > 0.0 {
+            for &(px, py) in self.points.iter() {
+                let circle = Circle {
+                    x: px,
+                    y: py,
+                    r: self.r,
+                };
+                if let Some(t) = ray_circle_intersection(x, y, dx, dy, &circle) {
+                    update(t);
+                }
+            }
+        }
+        hit
+    }
+
+    /// Distance between the skeletons, it's zero if their edges touch or cross
+    fn core_distance(&self, other: &Outline) -> f64 {
+        let mut distance = f64::INFINITY;
+        for (a, b) in segments(&self.points) {
+            for (c, d) in segments(&other.points) {
+                distance = distance.min(segment_distance(a, b, c, d));
+            }
+        }
+        distance
+    }
+
+    /// Shortest move along the normals of edges that separates intersecting skeletons
+    fn depth(&self, other: &Outline) -> f64 {
+        let project = |points: &[(f64, f64)], (nx, ny): (f64, f64)| {
+            points
+                .iter()
+                .map(|&(x, y)| x * nx + y * ny)
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+                    (min.min(p), max.max(p))
+                })
+        };
+        let axes = edges(&self.points)
+            .map(|(a, b)| self.outward_normal(a, b))
+            .chain(edges(&other.points).map(|(a, b)| other.outward_normal(a, b)))
+            .filter(|&normal| normal != (0.0, 0.0));
+        let depth = axes
+            .map(|axis| {
+                let (min1, max1) = project(&self.points, axis);
+                let (min2, max2) = project(&other.points, axis);
+                (max1 - min2).min(max2 - min1)
+            })
+            .fold(f64::INFINITY, f64::min);
+        if depth.is_finite() {
+            depth.max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Edges of the polygon, a segment has two edges facing opposite sides and a point has none
+fn edges(points: &[(f64, f64)]) -> impl Iterator<Item = ((f64, f64), (f64, f64))> + '_ {
+    let n = if points.len() >= 2 { points.len() } else { 0 };
+    (0..n).map(move |i| (points[i], points[(i + 1) % n]))
+}
+
+/// Edges of the polygon, a point is a single degenerate segment
+fn segments(points: &[(f64, f64)]) -> impl Iterator<Item = ((f64, f64), (f64, f64))> + '_ {
+    let point = (points.len() == 1).then(|| (points[0], points[0]));
+    point.into_iter().chain(edges(points))
+}
+
+fn closest_point(x: f64, y: f64, (x0, y0): (f64, f64), (x1, y1): (f64, f64)) -> (f64, f64) {
+    let (ex, ey) = (x1 - x0, y1 - y0);
+    let length = ex * ex + ey * ey;
+    if length == 0.0 {
+        return (x0, y0);
+    }
+    let t = (((x - x0) * ex + (y - y0) * ey) / length).clamp(0.0, 1.0);
+    (x0 + ex * t, y0 + ey * t)
+}
+
+fn cross((x0, y0): (f64, f64), (x1, y1): (f64, f64), (x, y): (f64, f64)) -> f64 {
+    (x1 - x0) * (y - y0) - (y1 - y0) * (x - x0)
+}
+
+fn segment_distance(a: (f64, f64), b: (f64, f64), c: (f64, f64), d: (f64, f64)) -> f64 {
+    if cross(a, b, c) * cross(a, b, d) < 0.0 && cross(c, d, a) * cross(c, d, b) < 0.0 {
+        return 0.0;
+    }
+    let point_distance = |(x, y): (f64, f64), p0, p1| {
+        let (qx, qy) = closest_point(x, y, p0, p1);
+        (x - qx).hypot(y - qy)
+    };
+    point_distance(a, c, d)
+        .min(point_distance(b, c, d))
+        .min(point_distance(c, a, b))
+        .min(point_distance(d, a, b))
+}
+
+/// Finds the distance along the ray with the unit direction `(dx, dy)` to the segment, a ray
+/// parallel to the segment misses it
+fn ray_segment_intersection(
+    x: f64,
+    y: f64,
+    dx: f64,
+    dy: f64,
+    (x0, y0): (f64, f64),
+    (x1, y1): (f64, f64),
+) -> Option<f64> {
+    let (ex, ey) = (x1 - x0, y1 - y0);
+    let denominator = dx * ey - dy * ex;
+    if denominator == 0.0 {
+        return None;
+    }
+    let (fx, fy) = (x0 - x, y0 - y);
+    let t = (fx * ey - fy * ex) / denominator;
+    let s = (fx * dy - fy * dx) / denominator;
+    (t >= 0.0 && (0.0..=1.0).contains(&s)).then_some(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::map::Barrier;
+
+    use super::Shape;
+
+    fn get_barrier(shape: Shape) -> Barrier {
+        Barrier {
+            x: 50.0,
+            y: 50.0,
+            shape,
+        }
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_rect() {
+        let rect = get_barrier(Shape::Rect {
+            width: 20.0,
+            height: 10.0,
+            angle: 0.0,
+        });
+        assert_close(rect.bounding_radius(), 125f64.sqrt());
+        assert_close(rect.distance(50.0, 50.0), -5.0);
+        assert_close(rect.distance(70.0, 50.0), 10.0);
+        assert_close(rect.distance(63.0, 59.0), 5.0);
+        assert_close(rect.ray_intersection(0.0, 50.0, 1.0, 0.0).unwrap(), 40.0);
+        assert_eq!(rect.ray_intersection(0.0, 56.0, 1.0, 0.0), None);
+        assert_eq!(rect.normal(40.0, 50.0, 1.0, 0.0), (-1.0, 0.0));
+
+        // Turned by 90 degrees it's as tall as it was wide
+        let rect = get_barrier(Shape::Rect {
+            width: 20.0,
+            height: 10.0,
+            angle: 90.0,
+        });
+        assert_close(rect.distance(50.0, 70.0), 10.0);
+        assert_close(rect.distance(70.0, 50.0), 15.0);
+    }
+
+    #[test]
+    fn test_polygon() {
+        // The winding order doesn't matter
+        for points in [
+            vec![(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)],
+            vec![(0.0, 0.0), (0.0, 10.0), (10.0, 0.0)],
+        ] {
+            let triangle = get_barrier(Shape::Polygon { points });
+            assert_close(triangle.distance(51.0, 51.0), -1.0);
+            assert_close(triangle.distance(50.0, 40.0), 10.0);
+            assert_close(
+                triangle.ray_intersection(50.0, 0.0, 0.0, 1.0).unwrap(),
+                50.0,
+            );
+            assert_eq!(triangle.normal(52.0, 50.0, 0.0, 1.0), (0.0, -1.0));
+            assert_eq!(triangle.ray_intersection(45.0, 0.0, 0.0, 1.0), None);
+        }
+    }
+
+    #[test]
+    fn test_polygon_validity() {
+        let valid = |points: &[(f64, f64)]| {
+            Shape::Polygon {
+                points: points.to_vec(),
+            }
+            .is_valid()
+        };
+        assert!(valid(&[(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)]));
+        assert!(valid(&[(0.0, 0.0), (0.0, 10.0), (10.0, 0.0)]));
+        assert!(!valid(&[(0.0, 0.0), (10.0, 0.0)]));
+        // Concave
+        assert!(!valid(&[(0.0, 0.0), (10.0, 0.0), (2.0, 2.0), (0.0, 10.0)]));
+        // Self-intersecting
+        assert!(!valid(&[
+            (0.0, 0.0),
+            (10.0, 10.0),
+            (10.0, 0.0),
+            (0.0, 10.0)
+        ]));
+        // A pentagram turns twice around its center
+        let star: Vec<(f64, f64)> = (0..5)
+            .map(|i| {
+                let angle = (i as f64 * 144.0).to_radians();
+                (angle.sin(), angle.cos())
+            })
+            .collect();
+        assert!(!valid(&star));
+    }
+
+    #[test]
+    fn test_capsule() {
+        let capsule = get_barrier(Shape::Capsule {
+            length: 20.0,
+            r: 5.0,
+            angle: 0.0,
+        });
+        assert_close(capsule.bounding_radius(), 15.0);
+        assert_close(capsule.distance(60.0, 55.0), 5.0);
+        assert_close(capsule.distance(50.0, 70.0), 5.0);
+        assert_close(capsule.distance(50.0, 50.0), -5.0);
+        assert_close(capsule.ray_intersection(0.0, 55.0, 1.0, 0.0).unwrap(), 45.0);
+        assert_close(
+            capsule.ray_intersection(50.0, 100.0, 0.0, -1.0).unwrap(),
+            35.0,
+        );
+        assert_eq!(capsule.normal(45.0, 50.0, 1.0, 0.0), (-1.0, 0.0));
+        assert_eq!(capsule.normal(50.0, 50.0, 1.0, 0.0), (-1.0, 0.0));
+        assert_close(
+            capsule
+                .segment_intersection(0.0, 50.0, 100.0, 50.0)
+                .unwrap(),
+            0.45,
+        );
+        assert_eq!(capsule.segment_intersection(0.0, 50.0, 40.0, 50.0), None);
+    }
+
+    #[test]
+    fn test_gap() {
+        let rect = get_barrier(Shape::Rect {
+            width: 20.0,
+            height: 20.0,
+            angle: 0.0,
+        });
+        let circle = Barrier::circle(75.0, 50.0, 5.0);
+        assert_close(rect.gap(&circle), 10.0);
+        assert_close(circle.gap(&rect), 10.0);
+        assert_close(rect.gap(&Barrier::circle(52.0, 50.0, 1.0)), -9.0);
+
+        // Crossing walls overlap though no vertex is inside the other wall, either is moved by
+        // half its length to separate them
+        let wall = get_barrier(Shape::Rect {
+            width: 40.0,
+            height: 2.0,
+            angle: 0.0,
+        });
+        let cross = get_barrier(Shape::Rect {
+            width: 2.0,
+            height: 40.0,
+            angle: 0.0,
+        });
+        assert_close(wall.gap(&cross), -21.0);
+    }
+}
@@ -0,0 +1,254 @@
+use std::error::Error;
+use std::fmt;
+
+use rand::rngs::StdRng;
+
+use super::events::GameEvent;
+use super::game::{Game, GameStatus};
+use super::health::Health;
+use super::player::{Missile, Player, PlayerStatus};
+use super::weapon::Weapon;
+
+/// State of a player that changes during a game
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerState {
+    pub id: usize,
+    pub x: f64,
+    pub y: f64,
+    pub direction: f64,
+    pub speed: f64,
+    pub vx: f64,
+    pub vy: f64,
+    pub thrust: f64,
+    pub turn: f64,
+    pub team: Option<usize>,
+    pub weapon: Weapon,
+    pub health: Health,
+    pub damage_dealt: f64,
+    pub kills: usize,
+    pub status: PlayerStatus,
+    random_direction: bool,
+}
+
+impl From<&Player> for PlayerState {
+    fn from(player: &Player) -> Self {
+        PlayerState {
+            id: player.id,
+            x: player.x,
+            y: player.y,
+            direction: player.direction,
+            speed: player.speed,
+            vx: player.vx,
+            vy: player.vy,
+            thrust: player.thrust,
+            turn: player.turn,
+            team: player.team,
+            weapon: player.weapon.clone(),
+            health: player.health.clone(),
+            damage_dealt: player.damage_dealt,
+            kills: player.kills,
+            status: player.status,
+            random_direction: player.random_direction,
+        }
+    }
+}
+
+impl PlayerState {
+    fn apply(&self, player: &mut Player) {
+        player.x = self.x;
+        player.y = self.y;
+        player.direction = self.direction;
+        player.speed = self.speed;
+        player.vx = self.vx;
+        player.vy = self.vy;
+        player.thrust = self.thrust;
+        player.turn = self.turn;
+        player.team = self.team;
+        player.weapon = self.weapon.clone();
+        player.health = self.health.clone();
+        player.damage_dealt = self.damage_dealt;
+        player.kills = self.kills;
+        player.status = self.status;
+        player.random_direction = self.random_direction;
+    }
+}
+
+/// Self-contained copy of everything a game changes while it runs, it can be restored into the
+/// game it was taken from. The map, the config and the registered players are not part of it.
+#[derive(Clone, Debug)]
+pub struct GameSnapshot {
+    pub players: Vec<PlayerState>,
+    pub missiles: Vec<Missile>,
+    pub events: Vec<GameEvent>,
+    pub status: GameStatus,
+    pub time: f64,
+    pending_time: f64,
+    rng: StdRng,
+    next_id: usize,
+}
+
+/// Why a snapshot can't be restored
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RestoreError {
+    /// The game's players differ from the snapshot's ones
+    PlayersMismatch,
+}
+
+impl fmt::Display for RestoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestoreError::PlayersMismatch => {
+                write!(f, "the players of the game differ from the snapshot")
+            }
+        }
+    }
+}
+
+impl Error for RestoreError {}
+
+impl Game {
+    pub fn snapshot(&self) -> GameSnapshot {
+        // Locked in the same order as players do on fire
+        let players = self
+            .players
+            .iter()
+            .map(|p| PlayerState::from(&*p.lock().unwrap()))
+            .collect();
+        let missiles = self.missiles.lock().unwrap().clone();
+        let events = self.events.lock().unwrap().events.clone();
+        GameSnapshot {
+            players,
+            missiles,
+            events,
+            status: self.status,
+            time: self.time,
+            pending_time: self.pending_time,
+            rng: self.rng.clone(),
+            next_id: self.ids.peek(),
+        }
+    }
+
+    /// Brings the game back to the snapshot, the players must be the ones registered when it
+    /// was taken
+    pub fn restore(&mut self, snapshot: &GameSnapshot) -> Result<(), RestoreError> {
+        let mut locked_players: Vec<_> = self.players.iter().map(|p| p.lock().unwrap()).collect();
+        if locked_players.len() != snapshot.players.len()
+            || locked_players
+                .iter()
+                .zip(snapshot.players.iter())
+                .any(|(p, state)| p.id != state.id)
+        {
+            return Err(RestoreError::PlayersMismatch);
+        }
+        for (player, state) in locked_players.iter_mut().zip(snapshot.players.iter()) {
+            state.apply(player);
+        }
+        drop(locked_players);
+
+        self.missiles.lock().unwrap().clone_from(&snapshot.missiles);
+        let mut events = self.events.lock().unwrap();
+        events.events.clone_from(&snapshot.events);
+        events.time = snapshot.time;
+        drop(events);
+
+        self.status = snapshot.status;
+        self.time = snapshot.time;
+        self.pending_time = snapshot.pending_time;
+        self.rng = snapshot.rng.clone();
+        self.ids.reset(snapshot.next_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{
+        config::GameConfig,
+        game::{Game, GameStatus, GameTrait, RegisterPlayer},
+        map::Map,
+        player::{Player, PlayerStatus, PlayerTrait},
+    };
+
+    use super::RestoreError;
+
+    fn get_game() -> (Arc<Mutex<Game>>, Vec<Arc<Mutex<Player>>>) {
+        let config = GameConfig {
+            seed: Some(1),
+            ..Default::default()
+        };
+        let game = Game::new(Map::new(300.0, 300.0, 10, 20.0, 1), config);
+        let players: Vec<_> = (0..3)
+            .map(|_| {
+                let (x, y) = game.lock().unwrap().get_free_point(5.0).unwrap();
+                Player::new(x, y, 5.0, 50.0, 60.0, 7, 200.0)
+            })
+            .collect();
+        for p in players.iter() {
+            game.register_player(p);
+        }
+        (game, players)
+    }
+
+    fn play(game: &mut Arc<Mutex<Game>>, players: &mut [Arc<Mutex<Player>>]) -> Vec<(f64, f64)> {
+        for i in 0..50 {
+            for p in players.iter_mut() {
+                p.rotate(7.0);
+                p.set_speed(50.0);
+                if i % 5 == 0 {
+                    let _ = p.fire();
+                }
+            }
+            game.process(0.05);
+        }
+        players.iter().map(|p| (p.get_x(), p.get_y())).collect()
+    }
+
+    #[test]
+    fn test_restore_replays_the_game() {
+        let (mut game, mut players) = get_game();
+        let snapshot = game.lock().unwrap().snapshot();
+
+        let first = play(&mut game, &mut players);
+        let first_missiles = game.lock().unwrap().missiles.lock().unwrap().clone();
+
+        game.lock().unwrap().restore(&snapshot).unwrap();
+        let second = play(&mut game, &mut players);
+
+        assert_eq!(first, second);
+        assert_eq!(
+            first_missiles,
+            *game.lock().unwrap().missiles.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_restore_game_over() {
+        let (game, players) = get_game();
+        let snapshot = game.lock().unwrap().snapshot();
+
+        for p in players.iter() {
+            p.lock().unwrap().status = PlayerStatus::KilledBy(0);
+        }
+        game.lock().unwrap().status = GameStatus::OverDraw;
+
+        game.lock().unwrap().restore(&snapshot).unwrap();
+        assert_eq!(game.lock().unwrap().status, GameStatus::On);
+        assert!(players
+            .iter()
+            .all(|p| p.lock().unwrap().status == PlayerStatus::InGame));
+    }
+
+    #[test]
+    fn test_restore_with_other_players() {
+        let (game, _) = get_game();
+        let snapshot = game.lock().unwrap().snapshot();
+
+        game.register_player(&Player::new(10.0, 10.0, 1.0, 1.0, 60.0, 7, 1.0));
+        assert_eq!(
+            game.lock().unwrap().restore(&snapshot),
+            Err(RestoreError::PlayersMismatch)
+        );
+    }
+}
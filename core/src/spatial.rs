@@ -0,0 +1,259 @@
+use super::geometry::Circle;
+
+/// Uniform grid over the map used as the broad phase of collision and ray queries. Every cell
+/// keeps the indices of the circles which bounding boxes overlap the cell, circles outside the
+/// map are kept in the edge cells.
+#[derive(Clone, Debug)]
+pub struct SpatialGrid {
+    cell_size: f64,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub fn new(width: f64, height: f64, cell_size: f64) -> Self {
+        let cell_size = if cell_size > 0.0 {
+            cell_size
+        } else {
+            width.max(height).max(1.0)
+        };
+        let columns = ((width / cell_size).ceil() as usize).max(1);
+        let rows = ((height / cell_size).ceil() as usize).max(1);
+        SpatialGrid {
+            cell_size,
+            columns,
+            rows,
+            cells: vec![Vec::new(); columns * rows],
+        }
+    }
+
+    /// Creates a grid with the cell size picked by the size and the density of the circles and
+    /// inserts them with their indices in the slice
+    pub fn from_circles(width: f64, height: f64, circles: &[Circle]) -> Self {
+        let max_radius = circles.iter().map(|c| c.r).fold(0.0, f64::max);
+        let density_size = (width * height / circles.len().max(1) as f64).sqrt();
+        let mut grid = Self::new(width, height, max_radius.max(density_size));
+        for (i, circle) in circles.iter().enumerate() {
+            grid.insert(i, circle);
+        }
+        grid
+    }
+
+    pub fn insert(&mut self, index: usize, circle: &Circle) {
+        let (c0, c1, r0, r1) = self.cell_range(
+            circle.x - circle.r,
+            circle.y - circle.r,
+            circle.x + circle.r,
+            circle.y + circle.r,
+        );
+        for row in r0..=r1 {
+            for column in c0..=c1 {
+                self.cells[row * self.columns + column].push(index);
+            }
+        }
+    }
+
+    /// Removes the index inserted with the same circle
+    pub fn remove(&mut self, index: usize, circle: &Circle) {
+        let (c0, c1, r0, r1) = self.cell_range(
+            circle.x - circle.r,
+            circle.y - circle.r,
+            circle.x + circle.r,
+            circle.y + circle.r,
+        );
+        for row in r0..=r1 {
+            for column in c0..=c1 {
+                self.cells[row * self.columns + column].retain(|&i| i != index);
+            }
+        }
+    }
+
+    /// Puts the sorted and deduplicated indices of the circles that may overlap the rectangle
+    /// into `out`
+    pub fn query_rect(&self, min_x: f64, min_y: f64, max_x: f64, max_y: f64, out: &mut Vec<usize>) {
+        out.clear();
+        let (c0, c1, r0, r1) = self.cell_range(min_x, min_y, max_x, max_y);
+        for row in r0..=r1 {
+            for column in c0..=c1 {
+                out.extend_from_slice(&self.cells[row * self.columns + column]);
+            }
+        }
+        out.sort_unstable();
+        out.dedup();
+    }
+
+    pub fn query_circle(&self, x: f64, y: f64, r: f64, out: &mut Vec<usize>) {
+        self.query_rect(x - r, y - r, x + r, y + r, out);
+    }
+
+    /// Queries the circles that may touch the segment moved by up to `margin` in any direction
+    pub fn query_segment(
+        &self,
+        x0: f64,
+        y0: f64,
+        x1: f64,
+        y1: f64,
+        margin: f64,
+        out: &mut Vec<usize>,
+    ) {
+        self.query_rect(
+            x0.min(x1) - margin,
+            y0.min(y1) - margin,
+            x0.max(x1) + margin,
+            y0.max(y1) + margin,
+            out,
+        );
+    }
+
+    /// Walks the cells along the ray with the unit direction `(dx, dy)` in order, calling `visit`
+    /// with the cell's indices and the distance where the ray leaves the cell. The walk stops when
+    /// `visit` returns `true` or the ray goes further than `max_distance`.
+    pub fn traverse_ray<F>(&self, x: f64, y: f64, dx: f64, dy: f64, max_distance: f64, mut visit: F)
+    where
+        F: FnMut(&[usize], f64) -> bool,
+    {
+        let mut column = self.column(x);
+        let mut row = self.row(y);
+
+        let (step_column, mut t_max_x, t_delta_x) = Self::ray_axis(x, dx, column, self.cell_size);
+        let (step_row, mut t_max_y, t_delta_y) = Self::ray_axis(y, dy, row, self.cell_size);
+
+        loop {
+            let t_exit = t_max_x.min(t_max_y);
+            if visit(&self.cells[row * self.columns + column], t_exit) || t_exit >= max_distance {
+                return;
+            }
+
+            if t_max_x < t_max_y {
+                match column.checked_add_signed(step_column) {
+                    Some(c) if c < self.columns => column = c,
+                    _ => return,
+                }
+                t_max_x += t_delta_x;
+            } else {
+                match row.checked_add_signed(step_row) {
+                    Some(r) if r < self.rows => row = r,
+                    _ => return,
+                }
+                t_max_y += t_delta_y;
+            }
+        }
+    }
+
+    // Step direction, distance to the first cell boundary and distance between boundaries
+    fn ray_axis(position: f64, direction: f64, cell: usize, cell_size: f64) -> (isize, f64, f64) {
+        if direction > 0.0 {
+            let boundary = (cell + 1) as f64 * cell_size;
+            (1, (boundary - position) / direction, cell_size / direction)
+        } else if direction < 0.0 {
+            let boundary = cell as f64 * cell_size;
+            (
+                -1,
+                (boundary - position) / direction,
+                -cell_size / direction,
+            )
+        } else {
+            (0, f64::INFINITY, f64::INFINITY)
+        }
+    }
+
+    fn column(&self, x: f64) -> usize {
+        ((x / self.cell_size).floor().max(0.0) as usize).min(self.columns - 1)
+    }
+
+    fn row(&self, y: f64) -> usize {
+        ((y / self.cell_size).floor().max(0.0) as usize).min(self.rows - 1)
+    }
+
+    fn cell_range(
+        &self,
+        min_x: f64,
+        min_y: f64,
+        max_x: f64,
+        max_y: f64,
+    ) -> (usize, usize, usize, usize) {
+        (
+            self.column(min_x),
+            self.column(max_x),
+            self.row(min_y),
+            self.row(max_y),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::Circle;
+
+    use super::SpatialGrid;
+
+    fn get_circles() -> Vec<Circle> {
+        vec![
+            Circle {
+                x: 10.0,
+                y: 10.0,
+                r: 5.0,
+            },
+            Circle {
+                x: 90.0,
+                y: 90.0,
+                r: 5.0,
+            },
+            Circle {
+                x: 50.0,
+                y: 50.0,
+                r: 30.0,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_query_circle() {
+        let grid = SpatialGrid::from_circles(100.0, 100.0, &get_circles());
+        let mut out = Vec::new();
+
+        grid.query_circle(10.0, 10.0, 1.0, &mut out);
+        assert!(out.contains(&0));
+        assert!(!out.contains(&1));
+
+        grid.query_circle(95.0, 95.0, 1.0, &mut out);
+        assert!(out.contains(&1));
+        assert!(!out.contains(&0));
+    }
+
+    #[test]
+    fn test_remove() {
+        let circles = get_circles();
+        let mut grid = SpatialGrid::from_circles(100.0, 100.0, &circles);
+        let mut out = Vec::new();
+
+        grid.remove(0, &circles[0]);
+        grid.query_rect(0.0, 0.0, 100.0, 100.0, &mut out);
+        assert_eq!(out, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_traverse_ray() {
+        let grid = SpatialGrid::new(100.0, 100.0, 10.0);
+
+        // A diagonal ray visits cells in order of the distance
+        let mut visited = 0;
+        let mut last_exit = 0.0;
+        grid.traverse_ray(5.0, 5.0, 0.6, 0.8, f64::INFINITY, |_, t_exit| {
+            assert!(t_exit >= last_exit);
+            last_exit = t_exit;
+            visited += 1;
+            false
+        });
+        assert!(visited > 10);
+
+        // The walk stops once the visitor asks for it
+        let mut visited = 0;
+        grid.traverse_ray(5.0, 5.0, 1.0, 0.0, f64::INFINITY, |_, _| {
+            visited += 1;
+            visited == 3
+        });
+        assert_eq!(visited, 3);
+    }
+}
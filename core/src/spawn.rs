@@ -0,0 +1,213 @@
+use std::error::Error;
+use std::fmt;
+
+use rand::Rng;
+
+use super::config::GameConfig;
+use super::map::{Map, SpawnPoint};
+use super::player::{Player, PlayerStatus};
+
+// Random points tried before the allocation gives up
+const MAX_SPAWN_ATTEMPTS: usize = 10000;
+
+/// Rules of placing new ships
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SpawnConfig {
+    /// Min distance between the hulls of a new ship and the other ships
+    pub min_distance: f64,
+    /// Whether new ships are kept out of the firing lines of the other ships
+    pub avoid_firing_lines: bool,
+}
+
+/// Why a ship couldn't be placed
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SpawnError {
+    /// The ship is larger than the map
+    TooLarge,
+    /// No free place was found
+    NoRoom,
+}
+
+impl fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpawnError::TooLarge => write!(f, "the ship doesn't fit the map"),
+            SpawnError::NoRoom => write!(f, "no free place for the ship"),
+        }
+    }
+}
+
+impl Error for SpawnError {}
+
+/// Finds a random point where a ship of radius `r` is inside the map and clear of every barrier
+/// and `accept` agrees with it
+pub(crate) fn random_point<R, F>(
+    map: &Map,
+    r: f64,
+    rng: &mut R,
+    accept: F,
+) -> Result<(f64, f64), SpawnError>
+where
+    R: Rng,
+    F: Fn(f64, f64) -> bool,
+{
+    if 2.0 * r > map.width || 2.0 * r > map.height {
+        return Err(SpawnError::TooLarge);
+    }
+    for _ in 0..MAX_SPAWN_ATTEMPTS {
+        let x = rng.gen_range(r..=map.width - r);
+        let y = rng.gen_range(r..=map.height - r);
+        if is_clear_of_barriers(map, x, y, r) && accept(x, y) {
+            return Ok((x, y));
+        }
+    }
+    Err(SpawnError::NoRoom)
+}
+
+/// Places a ship of radius `r` among the other ships. Free spawn points of the map are taken in
+/// order, a random point with a random direction is picked if the map has none left.
+pub(crate) fn allocate<R: Rng>(
+    map: &Map,
+    ships: &[&Player],
+    r: f64,
+    config: &GameConfig,
+    rng: &mut R,
+) -> Result<SpawnPoint, SpawnError> {
+    let ships: Vec<&Player> = ships
+        .iter()
+        .copied()
+        .filter(|p| p.status == PlayerStatus::InGame)
+        .collect();
+    let is_far = |x: f64, y: f64| {
+        ships
+            .iter()
+            .all(|p| (x - p.x).hypot(y - p.y) >= r + p.r + config.spawn.min_distance)
+    };
+
+    // Spawn points are fair by design, so only occupied ones are skipped
+    let spawn_point = map
+        .spawn_points
+        .iter()
+        .find(|s| is_far(s.x, s.y) && is_clear_of_barriers(map, s.x, s.y, r));
+    if let Some(spawn_point) = spawn_point {
+        return Ok(*spawn_point);
+    }
+
+    let (x, y) = random_point(map, r, rng, |x, y| {
+        is_far(x, y)
+            && !(config.spawn.avoid_firing_lines
+                && ships
+                    .iter()
+                    .any(|p| in_firing_line(p, x, y, r + config.missile_radius)))
+    })?;
+    Ok(SpawnPoint {
+        x,
+        y,
+        direction: rng.gen_range(-180f64..180f64),
+    })
+}
+
+fn is_clear_of_barriers(map: &Map, x: f64, y: f64, r: f64) -> bool {
+    map.barriers.iter().all(|b| b.distance(x, y) >= r)
+}
+
+/// Whether missiles of the ship pass closer than `clearance` to the point
+fn in_firing_line(ship: &Player, x: f64, y: f64, clearance: f64) -> bool {
+    let (dx, dy) = (
+        ship.direction.to_radians().sin(),
+        ship.direction.to_radians().cos(),
+    );
+    let (fx, fy) = (x - ship.x, y - ship.y);
+    let along = fx * dx + fy * dy;
+    along > 0.0 && (fx * dy - fy * dx).abs() < clearance
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::{
+        config::GameConfig,
+        map::{Barrier, Map, SpawnPoint},
+        player::Player,
+    };
+
+    use super::{allocate, SpawnConfig, SpawnError};
+
+    fn get_ship(x: f64, y: f64, direction: f64) -> Player {
+        Player::new_plain(x, y, 5.0, 10.0, 60.0, 1, Some(direction), 100.0)
+    }
+
+    #[test]
+    fn test_empty_map() {
+        let map = Map::new(100.0, 100.0, 0, 0.0, 1);
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!(map.get_free_point(5.0, &mut rng).is_ok());
+        assert_eq!(map.get_free_point(50.0, &mut rng), Ok((50.0, 50.0)));
+        assert_eq!(
+            map.get_free_point(51.0, &mut rng),
+            Err(SpawnError::TooLarge)
+        );
+    }
+
+    #[test]
+    fn test_avoids_every_barrier() {
+        let mut map = Map::new(100.0, 100.0, 0, 0.0, 1);
+        map.barriers.push(Barrier::circle(0.0, 0.0, 10.0));
+        map.barriers.push(Barrier::circle(50.0, 50.0, 40.0));
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..100 {
+            let (x, y) = map.get_free_point(5.0, &mut rng).unwrap();
+            for b in map.barriers.iter() {
+                assert!(b.distance(x, y) >= 5.0);
+            }
+        }
+
+        map.barriers.push(Barrier::circle(50.0, 50.0, 100.0));
+        assert_eq!(map.get_free_point(5.0, &mut rng), Err(SpawnError::NoRoom));
+    }
+
+    #[test]
+    fn test_avoids_ships() {
+        let map = Map::new(100.0, 100.0, 0, 0.0, 1);
+        let config = GameConfig {
+            spawn: SpawnConfig {
+                min_distance: 20.0,
+                avoid_firing_lines: true,
+            },
+            ..Default::default()
+        };
+        let ship = get_ship(10.0, 50.0, 90.0);
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..100 {
+            let spawn = allocate(&map, &[&ship], 5.0, &config, &mut rng).unwrap();
+            assert!((spawn.x - 10.0).hypot(spawn.y - 50.0) >= 30.0);
+            // The ship fires along the x axis
+            assert!((spawn.y - 50.0).abs() >= 5.0 + config.missile_radius);
+        }
+    }
+
+    #[test]
+    fn test_spawn_points() {
+        let mut map = Map::new(100.0, 100.0, 0, 0.0, 1);
+        for x in [20.0, 80.0] {
+            map.spawn_points.push(SpawnPoint {
+                x,
+                y: 50.0,
+                direction: 0.0,
+            });
+        }
+        let config = GameConfig::default();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let first = allocate(&map, &[], 5.0, &config, &mut rng).unwrap();
+        assert_eq!(first, map.spawn_points[0]);
+        let ship = get_ship(first.x, first.y, first.direction);
+        let second = allocate(&map, &[&ship], 5.0, &config, &mut rng).unwrap();
+        assert_eq!(second, map.spawn_points[1]);
+        let other = get_ship(second.x, second.y, second.direction);
+        let third = allocate(&map, &[&ship, &other], 5.0, &config, &mut rng).unwrap();
+        assert!(!map.spawn_points.contains(&third));
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::VecDeque;
+
+use super::map::{rotate, Barrier, Map, Symmetry};
+use super::spatial::SpatialGrid;
+
+// Upper bound of the samples of the ship's positions, it keeps tiny ships on big maps cheap
+const MAX_CELLS: f64 = 4_000_000.0;
+
+/// What keeps ships of a given radius from moving around a map
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapReport {
+    /// Pairs of indices of barriers that overlap each other
+    pub overlaps: Vec<(usize, usize)>,
+    /// Pairs of indices of separate barriers too close for a ship to pass between them
+    pub narrow_gaps: Vec<(usize, usize)>,
+    /// Amount of separate areas ships can move in, the map is connected if it's 1
+    pub regions: usize,
+    /// Area ships fit in but can't reach from the largest region
+    pub unreachable_area: f64,
+    /// Indices of spawn points where a ship doesn't fit or is cut off from the largest region
+    pub bad_spawn_points: Vec<usize>,
+}
+
+impl MapReport {
+    /// Whether a ship can get from any spawn point to any place it fits in
+    pub fn is_valid(&self) -> bool {
+        self.regions == 1 && self.bad_spawn_points.is_empty()
+    }
+}
+
+impl Map {
+    /// Checks where ships of `ship_radius` can move on the map. Positions are sampled on a grid
+    /// of half the ship's radius, so corridors barely wider than the ship may be seen as closed.
+    pub fn validate(&self, ship_radius: f64) -> MapReport {
+        let index = self.build_index();
+        let regions = Regions::new(self, &index, ship_radius);
+        let largest = regions.largest();
+
+        let mut overlaps = Vec::new();
+        let mut narrow_gaps = Vec::new();
+        let mut candidates = Vec::new();
+        for (i, a) in self.barriers.iter().enumerate() {
+            let radius = a.bounding_radius() + 2.0 * ship_radius;
+            index.query_circle(a.x, a.y, radius, &mut candidates);
+            for &j in candidates.iter().filter(|&&j| j > i) {
+                let gap = a.gap(&self.barriers[j]);
+                if gap < 0.0 {
+                    overlaps.push((i, j));
+                } else if gap < 2.0 * ship_radius {
+                    narrow_gaps.push((i, j));
+                }
+            }
+        }
+
+        let unreachable_cells = regions
+            .sizes
+            .iter()
+            .enumerate()
+            .filter(|&(region, _)| Some(region) != largest)
+            .map(|(_, size)| size)
+            .sum::<usize>();
+        let bad_spawn_points = self
+            .spawn_points
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| {
+                !fits(self, &index, s.x, s.y, ship_radius, &mut candidates)
+                    || largest.is_none()
+                    || !regions.touches(s.x, s.y, largest)
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        MapReport {
+            overlaps,
+            narrow_gaps,
+            regions: regions.sizes.len(),
+            unreachable_area: unreachable_cells as f64 * regions.cell_size.powi(2),
+            bad_spawn_points,
+        }
+    }
+
+    /// Removes barriers until ships of `ship_radius` fit at every spawn point and can reach every
+    /// place they fit in. Returns the removed barriers, a symmetric map may lose its symmetry.
+    pub fn repair(&mut self, ship_radius: f64) -> Vec<Barrier> {
+        let mut removed = Vec::new();
+
+        for i in 0..self.spawn_points.len() {
+            let spawn = self.spawn_points[i];
+            let (blocking, free): (Vec<Barrier>, Vec<Barrier>) = self
+                .barriers
+                .drain(..)
+                .partition(|b| b.distance(spawn.x, spawn.y) < ship_radius);
+            self.barriers = free;
+            removed.extend(blocking);
+        }
+
+        loop {
+            let index = self.build_index();
+            let regions = Regions::new(self, &index, ship_radius);
+            let Some(largest) = regions.largest() else {
+                break;
+            };
+            // The smallest region is opened first, removing one of its walls
+            let pocket = (0..regions.sizes.len())
+                .filter(|&region| region != largest)
+                .min_by_key(|&region| regions.sizes[region]);
+            let Some(wall) = pocket.and_then(|pocket| regions.wall(self, &index, pocket)) else {
+                break;
+            };
+            removed.push(self.barriers.remove(wall));
+        }
+        removed
+    }
+
+    /// Repairs a map generated by `new_symmetric` like `repair`, every copy of a removed barrier
+    /// is removed too, so the map keeps its symmetry
+    pub fn repair_symmetric(
+        &mut self,
+        ship_radius: f64,
+        symmetry: Symmetry,
+        players: usize,
+    ) -> Vec<Barrier> {
+        let (width, cx, cy) = (self.width, self.width / 2.0, self.height / 2.0);
+        let copies = |b: &Barrier| -> Vec<(f64, f64)> {
+            match symmetry {
+                Symmetry::Mirror => vec![(width - b.x, b.y)],
+                Symmetry::Rotational => {
+                    let order = players.max(1);
+                    (1..order)
+                        .map(|k| rotate(b.x, b.y, cx, cy, 360.0 * k as f64 / order as f64))
+                        .collect()
+                }
+            }
+        };
+
+        let mut removed = Vec::new();
+        loop {
+            let repaired = self.repair(ship_radius);
+            if repaired.is_empty() {
+                break;
+            }
+            let positions: Vec<(f64, f64)> = repaired.iter().flat_map(copies).collect();
+            let (copied, kept): (Vec<Barrier>, Vec<Barrier>) =
+                self.barriers.drain(..).partition(|b| {
+                    positions
+                        .iter()
+                        .any(|&(x, y)| (b.x - x).abs() < 1e-9 && (b.y - y).abs() < 1e-9)
+                });
+            self.barriers = kept;
+            removed.extend(repaired);
+            removed.extend(copied);
+        }
+        removed
+    }
+}
+
+/// Whether a ship fits at the point without touching barriers and borders
+fn fits(map: &Map, index: &SpatialGrid, x: f64, y: f64, r: f64, out: &mut Vec<usize>) -> bool {
+    if x < r || y < r || x > map.width - r || y > map.height - r {
+        return false;
+    }
+    index.query_circle(x, y, r, out);
+    out.iter().all(|&i| map.barriers[i].distance(x, y) >= r)
+}
+
+/// Positions of a ship's center sampled on a grid and split into connected regions
+struct Regions {
+    ship_radius: f64,
+    cell_size: f64,
+    columns: usize,
+    rows: usize,
+    /// Region of every cell, it's `None` if a ship doesn't fit there
+    labels: Vec<Option<usize>>,
+    /// Amount of cells of every region
+    sizes: Vec<usize>,
+}
+
+impl Regions {
+    fn new(map: &Map, index: &SpatialGrid, ship_radius: f64) -> Self {
+        // Cells are finer than the ship, so corridors it passes through are sampled
+        let cell_size = (ship_radius / 2.0).max((map.width * map.height / MAX_CELLS).sqrt());
+        let columns = ((map.width / cell_size).ceil() as usize).max(1);
+        let rows = ((map.height / cell_size).ceil() as usize).max(1);
+        let mut regions = Regions {
+            ship_radius,
+            cell_size,
+            columns,
+            rows,
+            labels: vec![None; columns * rows],
+            sizes: Vec::new(),
+        };
+
+        let mut out = Vec::new();
+        let free: Vec<bool> = (0..columns * rows)
+            .map(|cell| {
+                let (x, y) = regions.center(cell);
+                fits(map, index, x, y, ship_radius, &mut out)
+            })
+            .collect();
+
+        let mut queue = VecDeque::new();
+        for start in 0..free.len() {
+            if !free[start] || regions.labels[start].is_some() {
+                continue;
+            }
+            let region = regions.sizes.len();
+            regions.sizes.push(0);
+            regions.labels[start] = Some(region);
+            queue.push_back(start);
+            while let Some(cell) = queue.pop_front() {
+                regions.sizes[region] += 1;
+                for neighbour in regions.neighbours(cell) {
+                    if free[neighbour] && regions.labels[neighbour].is_none() {
+                        regions.labels[neighbour] = Some(region);
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+        }
+        regions
+    }
+
+    fn center(&self, cell: usize) -> (f64, f64) {
+        let (row, column) = (cell / self.columns, cell % self.columns);
+        (
+            (column as f64 + 0.5) * self.cell_size,
+            (row as f64 + 0.5) * self.cell_size,
+        )
+    }
+
+    fn neighbours(&self, cell: usize) -> impl Iterator<Item = usize> {
+        let (row, column) = (cell / self.columns, cell % self.columns);
+        let columns = self.columns;
+        [
+            (row > 0).then(|| cell - columns),
+            (row + 1 < self.rows).then(|| cell + columns),
+            (column > 0).then(|| cell - 1),
+            (column + 1 < columns).then(|| cell + 1),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    fn largest(&self) -> Option<usize> {
+        (0..self.sizes.len()).max_by_key(|&region| (self.sizes[region], usize::MAX - region))
+    }
+
+    /// Whether the cell of the point or a cell around it belongs to the region
+    fn touches(&self, x: f64, y: f64, region: Option<usize>) -> bool {
+        let column = ((x / self.cell_size) as usize).min(self.columns - 1);
+        let row = ((y / self.cell_size) as usize).min(self.rows - 1);
+        let cell = row * self.columns + column;
+        std::iter::once(cell)
+            .chain(self.neighbours(cell))
+            .any(|cell| self.labels[cell] == region)
+    }
+
+    /// Finds the smallest barrier that walls off the region
+    fn wall(&self, map: &Map, index: &SpatialGrid, region: usize) -> Option<usize> {
+        let mut out = Vec::new();
+        let mut wall: Option<usize> = None;
+        for cell in (0..self.labels.len()).filter(|&cell| self.labels[cell] == Some(region)) {
+            for neighbour in self.neighbours(cell).filter(|&n| self.labels[n].is_none()) {
+                let (x, y) = self.center(neighbour);
+                index.query_circle(x, y, self.ship_radius, &mut out);
+                for &i in out.iter() {
+                    let b = &map.barriers[i];
+                    let blocks = b.distance(x, y) < self.ship_radius;
+                    let smaller =
+                        |w: usize| map.barriers[w].bounding_radius() <= b.bounding_radius();
+                    if blocks && !matches!(wall, Some(w) if smaller(w)) {
+                        wall = Some(i);
+                    }
+                }
+            }
+        }
+        wall
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::map::{Barrier, Map, SpawnPoint, Symmetry};
+
+    fn get_pocket_map() -> Map {
+        // A ring of barriers around the center walls off a pocket
+        let mut map = Map::new(200.0, 200.0, 0, 0.0, 1);
+        for i in 0..12 {
+            let angle = (i as f64 * 30.0).to_radians();
+            map.barriers.push(Barrier::circle(
+                100.0 + 40.0 * angle.sin(),
+                100.0 + 40.0 * angle.cos(),
+                8.0,
+            ));
+        }
+        map
+    }
+
+    #[test]
+    fn test_connected_map() {
+        let map = Map::new(200.0, 200.0, 0, 0.0, 1);
+        let report = map.validate(5.0);
+        assert!(report.is_valid());
+        assert_eq!(report.regions, 1);
+        assert_eq!(report.unreachable_area, 0.0);
+    }
+
+    #[test]
+    fn test_pocket() {
+        let mut map = get_pocket_map();
+        map.spawn_points.push(SpawnPoint {
+            x: 100.0,
+            y: 100.0,
+            direction: 0.0,
+        });
+        let report = map.validate(5.0);
+        assert!(!report.is_valid());
+        assert_eq!(report.regions, 2);
+        assert!(report.unreachable_area > 0.0);
+        assert_eq!(report.bad_spawn_points, vec![0]);
+        assert_eq!(report.narrow_gaps.len(), 12);
+        assert!(report.overlaps.is_empty());
+
+        // Small ships slip between the barriers
+        assert!(map.validate(1.0).is_valid());
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut map = get_pocket_map();
+        map.spawn_points.push(SpawnPoint {
+            x: 100.0 + 40.0,
+            y: 100.0,
+            direction: 0.0,
+        });
+        let removed = map.repair(5.0);
+        assert!(!removed.is_empty());
+        assert!(map.validate(5.0).is_valid());
+    }
+
+    #[test]
+    fn test_generation_with_gaps() {
+        let map = Map::new_with_gaps(1000.0, 1000.0, 300, 60.0, 1, 20.0);
+        assert!(!map.barriers.is_empty());
+        let report = map.validate(5.0);
+        assert!(report.overlaps.is_empty());
+        assert!(report.narrow_gaps.is_empty());
+        assert!(report.is_valid());
+        for b in map.barriers.iter() {
+            let r = b.bounding_radius();
+            assert!(r <= 60.0);
+            assert!(b.x - r >= 20.0 && b.x + r <= 980.0);
+            assert!(b.y - r >= 20.0 && b.y + r <= 980.0);
+        }
+    }
+
+    #[test]
+    fn test_symmetric_repair() {
+        for (symmetry, players) in [(Symmetry::Mirror, 2), (Symmetry::Rotational, 3)] {
+            let mut map = Map::new_symmetric(600.0, 600.0, 60, 40.0, 1, symmetry, players);
+            let mut repaired = map.clone();
+            assert!(!repaired.repair_symmetric(5.0, symmetry, players).is_empty());
+            assert!(repaired.validate(5.0).is_valid());
+
+            // What's left is what a symmetric map with the same barriers would keep
+            let order = if symmetry == Symmetry::Mirror {
+                2
+            } else {
+                players
+            };
+            assert_eq!(repaired.barriers.len() % order, 0);
+            map.barriers.retain(|b| repaired.barriers.contains(b));
+            assert_eq!(map.barriers, repaired.barriers);
+        }
+    }
+}
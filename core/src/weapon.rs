@@ -0,0 +1,161 @@
+use std::error::Error;
+use std::fmt;
+
+// Cooldowns are counted down by time steps, so they may stop a bit above zero
+const COOLDOWN_EPSILON: f64 = 1e-9;
+
+/// Regenerating energy pool the shots are paid from
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EnergyConfig {
+    pub capacity: f64,
+    /// Energy restored per second
+    pub regeneration: f64,
+    pub shot_cost: f64,
+}
+
+/// Weapon settings shared by every player of a game, the default one has no limits
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct WeaponConfig {
+    /// Min time between shots in seconds
+    pub cooldown: f64,
+    /// Amount of shots per game, unlimited if it's not set
+    pub ammo: Option<u32>,
+    pub energy: Option<EnergyConfig>,
+}
+
+/// Why a shot didn't happen
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FireError {
+    /// The player isn't registered in a game or is out of it
+    Inactive,
+    Cooldown,
+    NoAmmo,
+    NoEnergy,
+}
+
+impl fmt::Display for FireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FireError::Inactive => write!(f, "the player is not in game"),
+            FireError::Cooldown => write!(f, "the weapon is cooling down"),
+            FireError::NoAmmo => write!(f, "no ammo left"),
+            FireError::NoEnergy => write!(f, "not enough energy"),
+        }
+    }
+}
+
+impl Error for FireError {}
+
+/// Weapon state of a player
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Weapon {
+    pub config: WeaponConfig,
+    /// Time left until the next shot
+    pub cooldown: f64,
+    pub ammo: Option<u32>,
+    pub energy: f64,
+}
+
+impl Weapon {
+    /// Creates a loaded weapon with the full energy pool
+    pub fn new(config: WeaponConfig) -> Self {
+        Weapon {
+            config,
+            cooldown: 0.0,
+            ammo: config.ammo,
+            energy: config.energy.map_or(0.0, |e| e.capacity),
+        }
+    }
+
+    /// Cools the weapon down and regenerates energy
+    pub fn tick(&mut self, timedelta: f64) {
+        self.cooldown = (self.cooldown - timedelta).max(0.0);
+        if let Some(energy) = self.config.energy {
+            self.energy = (self.energy + energy.regeneration * timedelta).min(energy.capacity);
+        }
+    }
+
+    /// Spends a shot if the weapon is ready
+    pub fn shoot(&mut self) -> Result<(), FireError> {
+        if self.cooldown > COOLDOWN_EPSILON {
+            return Err(FireError::Cooldown);
+        }
+        if self.ammo == Some(0) {
+            return Err(FireError::NoAmmo);
+        }
+        if let Some(energy) = self.config.energy {
+            if self.energy < energy.shot_cost {
+                return Err(FireError::NoEnergy);
+            }
+            self.energy -= energy.shot_cost;
+        }
+        if let Some(ammo) = self.ammo.as_mut() {
+            *ammo -= 1;
+        }
+        self.cooldown = self.config.cooldown;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EnergyConfig, FireError, Weapon, WeaponConfig};
+
+    #[test]
+    fn test_unlimited_weapon() {
+        let mut weapon = Weapon::new(WeaponConfig::default());
+        for _ in 0..100 {
+            assert_eq!(weapon.shoot(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let mut weapon = Weapon::new(WeaponConfig {
+            cooldown: 0.5,
+            ..Default::default()
+        });
+        assert_eq!(weapon.shoot(), Ok(()));
+        assert_eq!(weapon.shoot(), Err(FireError::Cooldown));
+        for _ in 0..4 {
+            weapon.tick(0.1);
+            assert_eq!(weapon.shoot(), Err(FireError::Cooldown));
+        }
+        weapon.tick(0.1);
+        assert_eq!(weapon.shoot(), Ok(()));
+    }
+
+    #[test]
+    fn test_ammo() {
+        let mut weapon = Weapon::new(WeaponConfig {
+            ammo: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(weapon.shoot(), Ok(()));
+        assert_eq!(weapon.shoot(), Ok(()));
+        assert_eq!(weapon.shoot(), Err(FireError::NoAmmo));
+        weapon.tick(100.0);
+        assert_eq!(weapon.shoot(), Err(FireError::NoAmmo));
+    }
+
+    #[test]
+    fn test_energy() {
+        let mut weapon = Weapon::new(WeaponConfig {
+            energy: Some(EnergyConfig {
+                capacity: 10.0,
+                regeneration: 2.0,
+                shot_cost: 4.0,
+            }),
+            ..Default::default()
+        });
+        assert_eq!(weapon.shoot(), Ok(()));
+        assert_eq!(weapon.shoot(), Ok(()));
+        assert_eq!(weapon.shoot(), Err(FireError::NoEnergy));
+        weapon.tick(1.0);
+        assert_eq!(weapon.shoot(), Ok(()));
+
+        // The pool doesn't overflow its capacity
+        weapon.tick(100.0);
+        assert_eq!(weapon.energy, 10.0);
+    }
+}
@@ -0,0 +1,379 @@
+use std::error::Error;
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use super::config::GameConfig;
+use super::events::{EventLog, GameEvent};
+use super::game::{run_steps, step, Arena, GameStatus};
+use super::map::{Map, SpawnPoint};
+use super::player::{
+    observe, IdAllocator, Missile, Player, PlayerTrait, RayObservation, SelfState, ViewHit,
+};
+use super::sensors::{add_noise, noise_seed, scan, RadarContact};
+use super::spatial::SpatialGrid;
+use super::spawn::{allocate, SpawnError};
+use super::weapon::FireError;
+
+/// Handle of a player added to a `World`, it's the player's id
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PlayerId(pub usize);
+
+/// Command of a player
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Action {
+    Rotate(f64),
+    SetSpeed(f64),
+    /// Thrust and turn of the Newtonian flight model
+    Thrust {
+        thrust: f64,
+        turn: f64,
+    },
+    Fire,
+}
+
+/// Why an action didn't happen
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ActionError {
+    /// The world has no player with this id
+    UnknownPlayer,
+    Fire(FireError),
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionError::UnknownPlayer => write!(f, "unknown player"),
+            ActionError::Fire(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for ActionError {}
+
+impl From<FireError> for ActionError {
+    fn from(err: FireError) -> Self {
+        ActionError::Fire(err)
+    }
+}
+
+/// Game that owns its players and addresses them by `PlayerId`. It runs the same simulation as
+/// `Game` without any locks, and it's cheap to clone for lookahead.
+#[derive(Clone)]
+pub struct World {
+    pub map: Map,
+    pub config: GameConfig,
+    /// Players in the order they were added
+    pub players: Vec<Player>,
+    pub missiles: Vec<Missile>,
+    pub events: EventLog,
+    pub status: GameStatus,
+    /// Seed of the world's RNG used for spawns and random directions
+    pub seed: u64,
+    pub rng: StdRng,
+    pub ids: IdAllocator,
+    /// Elapsed game time in seconds
+    pub time: f64,
+    // Time passed to `step` but not simulated yet
+    pending_time: f64,
+    // Broad phase index of `map.barriers`
+    barriers_index: SpatialGrid,
+}
+
+impl World {
+    pub fn new(map: Map, config: GameConfig) -> Self {
+        let seed = config.seed.unwrap_or_else(rand::random::<u64>);
+        World {
+            barriers_index: map.build_index(),
+            map,
+            config,
+            players: Vec::new(),
+            missiles: Vec::new(),
+            events: EventLog::default(),
+            status: GameStatus::On,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            ids: IdAllocator::new(),
+            time: 0.0,
+            pending_time: 0.0,
+        }
+    }
+
+    /// Must be called after `map.barriers` are changed
+    pub fn rebuild_index(&mut self) {
+        self.barriers_index = self.map.build_index();
+    }
+
+    pub fn add_player(&mut self, mut player: Player) -> PlayerId {
+        player.join(&self.config, &self.ids, &mut self.rng);
+        let id = PlayerId(player.id);
+        self.players.push(player);
+        id
+    }
+
+    pub fn player(&self, id: PlayerId) -> Option<&Player> {
+        self.players.iter().find(|p| p.id == id.0)
+    }
+
+    pub fn player_mut(&mut self, id: PlayerId) -> Option<&mut Player> {
+        self.players.iter_mut().find(|p| p.id == id.0)
+    }
+
+    /// Applies the player's command, it takes effect on the next step
+    pub fn apply(&mut self, id: PlayerId, action: Action) -> Result<(), ActionError> {
+        let player = self
+            .players
+            .iter_mut()
+            .find(|p| p.id == id.0)
+            .ok_or(ActionError::UnknownPlayer)?;
+        match action {
+            Action::Rotate(angle) => player.rotate(angle),
+            Action::SetSpeed(speed) => player.set_speed(speed),
+            Action::Thrust { thrust, turn } => {
+                player.set_thrust(thrust);
+                player.set_turn(turn);
+            }
+            Action::Fire => {
+                let (missile, event) = player.shoot(&self.ids)?;
+                self.missiles.push(missile);
+                self.events.push(event);
+            }
+        }
+        Ok(())
+    }
+
+    /// What the player's rays hit, it's `None` if the world has no such player
+    pub fn view(&self, id: PlayerId) -> Option<Vec<ViewHit>> {
+        let view = self.view_extended(id)?;
+        Some(view.into_iter().map(|o| o.hit).collect())
+    }
+
+    /// Same rays as `view` with what exactly they hit
+    pub fn view_extended(&self, id: PlayerId) -> Option<Vec<RayObservation>> {
+        let player = self.player(id)?;
+        let others: Vec<&Player> = self.players.iter().filter(|p| p.id != id.0).collect();
+        let mut view = observe(
+            player,
+            &others,
+            &self.missiles,
+            &self.map,
+            &self.barriers_index,
+            &self.config,
+        );
+        if let Some(noise) = player.sensor_noise.or(self.config.sensor_noise) {
+            add_noise(
+                &mut view,
+                &noise,
+                noise_seed(self.seed, player.id, self.time),
+            );
+        }
+        Some(view)
+    }
+
+    /// State of the player, it's `None` if the world has no such player
+    pub fn self_state(&self, id: PlayerId) -> Option<SelfState> {
+        Some(self.player(id)?.self_state(&self.map, self.time))
+    }
+
+    /// Ships and missiles around the player, it's `None` if the world has no such player
+    pub fn radar(&self, id: PlayerId) -> Option<Vec<RadarContact>> {
+        let player = self.player(id)?;
+        let others: Vec<&Player> = self.players.iter().filter(|p| p.id != id.0).collect();
+        Some(scan(
+            player,
+            &others,
+            &self.missiles,
+            &self.map,
+            &self.barriers_index,
+            &self.config,
+        ))
+    }
+
+    /// Simulates `time` seconds, the same way `Game::process` does
+    pub fn step(&mut self, time: f64) {
+        self.pending_time += time;
+        let World {
+            ref map,
+            ref config,
+            ref barriers_index,
+            ref mut players,
+            ref mut missiles,
+            ref mut events,
+            ref mut status,
+            ref mut time,
+            ref mut pending_time,
+            ..
+        } = *self;
+        let arena = Arena {
+            map,
+            barriers_index,
+            config,
+        };
+
+        run_steps(config, time, pending_time, |timedelta, time| {
+            let mut players: Vec<&mut Player> = players.iter_mut().collect();
+            let mut step_events = Vec::new();
+            step(
+                &arena,
+                &mut players,
+                missiles,
+                status,
+                time,
+                timedelta,
+                &mut step_events,
+            );
+            events.time = time;
+            events.events.extend(step_events);
+            *status == GameStatus::On
+        });
+    }
+
+    /// Takes the events recorded since the last call
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        self.events.drain()
+    }
+
+    /// Finds a point free of barriers using the world's RNG
+    pub fn get_free_point(&mut self, r: f64) -> Result<(f64, f64), SpawnError> {
+        self.map.get_free_point(r, &mut self.rng)
+    }
+
+    /// Places a new ship of radius `r` the same way `Game::spawn_point` does
+    pub fn spawn_point(&mut self, r: f64) -> Result<SpawnPoint, SpawnError> {
+        let ships: Vec<&Player> = self.players.iter().collect();
+        allocate(&self.map, &ships, r, &self.config, &mut self.rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::GameConfig,
+        game::{Game, GameStatus, GameTrait, RegisterPlayer},
+        map::Map,
+        player::{Player, PlayerStatus, PlayerTrait, ViewHit},
+        weapon::FireError,
+    };
+
+    use super::{Action, ActionError, PlayerId, World};
+
+    fn get_player(x: f64, y: f64, direction: f64) -> Player {
+        Player::new_plain(x, y, 1.0, 10.0, 60.0, 1, Some(direction), 100.0)
+    }
+
+    fn get_duel() -> (World, PlayerId, PlayerId) {
+        let mut world = World::new(
+            Map::new(100.0, 100.0, 0, 0.0, 1),
+            GameConfig {
+                seed: Some(1),
+                ..Default::default()
+            },
+        );
+        let p = world.add_player(get_player(50.0, 10.0, 0.0));
+        let target = world.add_player(get_player(50.0, 60.0, 180.0));
+        (world, p, target)
+    }
+
+    #[test]
+    fn test_apply_and_step() {
+        let (mut world, p, _) = get_duel();
+        world.apply(p, Action::SetSpeed(5.0)).unwrap();
+        world.step(1.0);
+        world.apply(p, Action::Rotate(90.0)).unwrap();
+        world.step(1.0);
+
+        let player = world.player(p).unwrap();
+        assert!((player.x - 55.0).abs() < 1e-9);
+        assert!((player.y - 15.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_view_and_fire() {
+        let (mut world, p, target) = get_duel();
+        assert_eq!(world.view(p), Some(vec![ViewHit::Enemy(48.0)]));
+
+        world.apply(p, Action::Fire).unwrap();
+        world.step(1.0);
+
+        assert_eq!(world.status, GameStatus::Over(p.0));
+        assert_eq!(
+            world.player(target).unwrap().status,
+            PlayerStatus::KilledBy(p.0)
+        );
+        assert_eq!(
+            world.apply(p, Action::Fire),
+            Err(ActionError::Fire(FireError::Inactive))
+        );
+    }
+
+    #[test]
+    fn test_unknown_player() {
+        let (mut world, _, _) = get_duel();
+        assert_eq!(
+            world.apply(PlayerId(100), Action::Fire),
+            Err(ActionError::UnknownPlayer)
+        );
+        assert_eq!(world.view(PlayerId(100)), None);
+    }
+
+    #[test]
+    fn test_clone_looks_ahead() {
+        let (mut world, p, _) = get_duel();
+        world.apply(p, Action::SetSpeed(5.0)).unwrap();
+
+        let mut lookahead = world.clone();
+        lookahead.apply(p, Action::Fire).unwrap();
+        lookahead.step(1.0);
+        assert_eq!(lookahead.status, GameStatus::Over(p.0));
+
+        assert_eq!(world.status, GameStatus::On);
+        assert!(world.missiles.is_empty());
+        assert_eq!(world.player(p).unwrap().y, 10.0);
+    }
+
+    #[test]
+    fn test_same_as_game() {
+        let config = GameConfig {
+            seed: Some(7),
+            ..Default::default()
+        };
+        let map = Map::new(300.0, 300.0, 10, 20.0, 1);
+
+        let mut world = World::new(map.clone(), config.clone());
+        let mut game = Game::new(map, config);
+        let mut ids = Vec::new();
+        let mut players = Vec::new();
+        for _ in 0..3 {
+            let (x, y) = world.get_free_point(5.0).unwrap();
+            ids.push(world.add_player(Player::new_plain(x, y, 5.0, 50.0, 60.0, 7, None, 200.0)));
+            let (x, y) = game.lock().unwrap().get_free_point(5.0).unwrap();
+            let p = Player::new(x, y, 5.0, 50.0, 60.0, 7, 200.0);
+            game.register_player(&p);
+            players.push(p);
+        }
+
+        for i in 0..50 {
+            for (&id, p) in ids.iter().zip(players.iter_mut()) {
+                world.apply(id, Action::Rotate(7.0)).unwrap();
+                world.apply(id, Action::SetSpeed(50.0)).unwrap();
+                p.rotate(7.0);
+                p.set_speed(50.0);
+                if i % 5 == 0 {
+                    let _ = world.apply(id, Action::Fire);
+                    let _ = p.fire();
+                }
+            }
+            world.step(0.05);
+            game.process(0.05);
+        }
+
+        for (w, p) in world.players.iter().zip(players.iter()) {
+            assert_eq!(
+                (w.x, w.y, w.status),
+                (p.get_x(), p.get_y(), p.lock().unwrap().status)
+            );
+        }
+        assert_eq!(world.drain_events(), game.lock().unwrap().drain_events());
+    }
+}
@@ -1,3 +1,3 @@
-from .space_drive_game import Game, Map, Player
+from .space_drive_game import Game, Map, Player, Snapshot
 
-__all__ = ['Game', 'Map', 'Player']
+__all__ = ['Game', 'Map', 'Player', 'Snapshot']
@@ -1,9 +1,22 @@
-from typing import Literal, Sequence, Union
+from typing import Any, Literal, Sequence, Union
 
 class Map:
-    def __new__(cls, width: float, height: float, barriers_amount: int, max_barrier_radius: float, seed: Union[int, None] = None) -> Map: ...
+    def __new__(
+            cls,
+            width: float,
+            height: float,
+            barriers_amount: int,
+            max_barrier_radius: float,
+            seed: Union[int, None] = None,
+            symmetry: Union[Literal['mirror', 'rotational'], None] = None,
+            players: int = 2,
+            min_gap: Union[float, None] = None,
+    ) -> Map: ...
     def get_barriers(self) -> Sequence[tuple[float, float, float]]: ...
-    def get_free_point(self, r: float) -> tuple[float, float]: ...
+    def get_spawn_points(self) -> Sequence[tuple[float, float, float]]: ...
+    def validate(self, ship_radius: float) -> dict[str, Any]: ...
+    def repair(self, ship_radius: float) -> Sequence[tuple[float, float, float]]: ...
+    def get_free_point(self, r: float, seed: Union[int, None] = None) -> tuple[float, float]: ...
     @property
     def seed(self) -> int: ...
 
@@ -22,25 +35,100 @@ class Player:
         ) -> Player: ...
     def rotate(self, angle: float) -> None: ...
     def set_speed(self, speed: float) -> None: ...
+    def set_thrust(self, thrust: float) -> None: ...
+    def set_turn(self, turn: float) -> None: ...
+    def set_team(self, team: int) -> None: ...
+    @property
+    def id(self) -> int: ...
+    @property
+    def team(self) -> int: ...
     @property
     def direction(self) -> float: ...
     @property
     def speed(self) -> float: ...
     @property
+    def velocity(self) -> tuple[float, float]: ...
+    @property
     def x(self) -> float: ...
     @property
     def y(self) -> float: ...
     @property
-    def status(self) -> Literal['[DEAD]', '[INGAME]', '[WIN]']: ...
-    def view(self) -> Sequence[tuple[Literal['[BORDER]', '[BARRIER]', '[ENEMY]'], float]]: ...
-    def fire(self) -> None: ...
+    def status(self) -> Literal['[DEAD]', '[INGAME]', '[WIN]', '[LOSE]', '[DRAW]']: ...
+    def set_sensor_noise(self, distance_std: float = 0.0, dropout: float = 0.0, misclassification: float = 0.0) -> None: ...
+    def view(self) -> Sequence[tuple[Literal['[BORDER]', '[BARRIER]', '[ENEMY]', '[ALLY]', '[MISSILE]', '[UNKNOWN]'], Union[float, None]]]: ...
+    def view_extended(self) -> Sequence[dict[str, Any]]: ...
+    def radar(self) -> Sequence[dict[str, Any]]: ...
+    def fire(self) -> bool: ...
+    @property
+    def cooldown(self) -> float: ...
+    @property
+    def ammo(self) -> Union[int, None]: ...
+    @property
+    def energy(self) -> float: ...
+    @property
+    def hp(self) -> float: ...
+    @property
+    def shield(self) -> float: ...
+    @property
+    def damage_dealt(self) -> float: ...
+    @property
+    def kills(self) -> int: ...
+
+
+class Snapshot: ...
 
 
 class Game:
-    def __new__(cls, map: Map) -> Game: ...
+    def __new__(
+            cls,
+            map: Map,
+            time_step: Union[float, None] = None,
+            max_substeps: Union[int, None] = None,
+            seed: Union[int, None] = None,
+            flight_model: Literal['arcade', 'newtonian'] = 'arcade',
+            acceleration: Union[float, None] = None,
+            max_angular_velocity: Union[float, None] = None,
+            drag: Union[float, None] = None,
+            cooldown: float = 0.0,
+            ammo: Union[int, None] = None,
+            energy_capacity: Union[float, None] = None,
+            energy_regeneration: float = 1.0,
+            shot_cost: float = 1.0,
+            missile_range: Union[float, None] = None,
+            missile_ttl: Union[float, None] = None,
+            missile_interception: bool = False,
+            missile_radius: Union[float, None] = None,
+            missiles_visible: bool = False,
+            sensor_distance_std: float = 0.0,
+            sensor_dropout: float = 0.0,
+            sensor_misclassification: float = 0.0,
+            radar_radius: Union[float, None] = None,
+            radar_ids: bool = True,
+            radar_line_of_sight: bool = False,
+            spawn_min_distance: float = 0.0,
+            avoid_firing_lines: bool = False,
+            hp: Union[float, None] = None,
+            shield_capacity: Union[float, None] = None,
+            shield_regeneration: float = 1.0,
+            missile_damage: Union[float, None] = None,
+            friendly_fire: bool = False,
+            time_limit: Union[float, None] = None,
+            tiebreak: Literal['kills', 'damage', 'draw'] = 'draw',
+        ) -> Game: ...
     def register_player(self, player: Player) -> None: ...
+    def spawn_player(self, player: Player) -> None: ...
+    def get_free_point(self, r: float) -> tuple[float, float]: ...
+    @property
+    def seed(self) -> int: ...
     def process(time: float) -> None: ...
     def get_missiles(self) -> Sequence[tuple[float, float]]: ...
+    def drain_events(self) -> Sequence[dict[str, Any]]: ...
+    def snapshot(self) -> Snapshot: ...
+    def restore(self, snapshot: Snapshot) -> None: ...
+    @property
+    def time(self) -> float: ...
+    @property
+    def winner_team(self) -> Union[int, None]: ...
     @property
     def status(self) -> Literal['[ON]', '[OVER]']: ...
  
\ No newline at end of file
@@ -1,12 +1,51 @@
+// `#[pymethods]` of pyo3 0.21 puts impls of methods with `#[pyo3(signature = ...)]` into a const
+// block next to the item, newer toolchains flag it with `non_local_definitions`
+#![allow(non_local_definitions)]
+
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 use std::sync::{Arc, Mutex};
 
+#[rustfmt::skip]
+use space_drive_game_core::config::{
+    GameConfig      as _GameConfig,
+};
+#[rustfmt::skip]
+use space_drive_game_core::events::{
+    Event           as _Event,
+    GameEvent       as _GameEvent,
+};
 #[rustfmt::skip]
 use space_drive_game_core::game::{
+    FlightModel     as _FlightModel,
     Game            as _Game,
     GameTrait       as _GameTrait,
     GameStatus      as _GameStatus,
+    NewtonianConfig as _NewtonianConfig,
     RegisterPlayer  as _RegisterPlayer,
+    Tiebreak        as _Tiebreak,
+};
+#[rustfmt::skip]
+use space_drive_game_core::sensors::{
+    RadarConfig     as _RadarConfig,
+    SensorNoise     as _SensorNoise,
+};
+#[rustfmt::skip]
+use space_drive_game_core::spawn::{
+    SpawnConfig     as _SpawnConfig,
+};
+#[rustfmt::skip]
+use space_drive_game_core::snapshot::{
+    GameSnapshot    as _GameSnapshot,
+};
+#[rustfmt::skip]
+use space_drive_game_core::health::{
+    ShieldConfig    as _ShieldConfig,
+};
+#[rustfmt::skip]
+use space_drive_game_core::weapon::{
+    EnergyConfig    as _EnergyConfig,
 };
 
 use super::map::Map;
@@ -15,11 +54,140 @@ use super::player::Player;
 #[pyclass]
 pub struct Game(Arc<Mutex<_Game>>);
 
+/// Opaque game state returned by `Game.snapshot`
+#[pyclass]
+pub struct Snapshot(_GameSnapshot);
+
 #[pymethods]
 impl Game {
+    #[allow(clippy::too_many_arguments)]
     #[new]
-    pub fn new(map: &Map) -> Self {
-        Game(_Game::new(map.0.clone()))
+    #[pyo3(signature = (map, time_step = None, max_substeps = None, seed = None, flight_model = "arcade", acceleration = None, max_angular_velocity = None, drag = None, cooldown = 0.0, ammo = None, energy_capacity = None, energy_regeneration = 1.0, shot_cost = 1.0, missile_range = None, missile_ttl = None, missile_interception = false, missile_radius = None, missiles_visible = false, sensor_distance_std = 0.0, sensor_dropout = 0.0, sensor_misclassification = 0.0, radar_radius = None, radar_ids = true, radar_line_of_sight = false, spawn_min_distance = 0.0, avoid_firing_lines = false, hp = None, shield_capacity = None, shield_regeneration = 1.0, missile_damage = None, friendly_fire = false, time_limit = None, tiebreak = "draw"))]
+    pub fn new(
+        map: &Map,
+        time_step: Option<f64>,
+        max_substeps: Option<usize>,
+        seed: Option<u64>,
+        flight_model: &str,
+        acceleration: Option<f64>,
+        max_angular_velocity: Option<f64>,
+        drag: Option<f64>,
+        cooldown: f64,
+        ammo: Option<u32>,
+        energy_capacity: Option<f64>,
+        energy_regeneration: f64,
+        shot_cost: f64,
+        missile_range: Option<f64>,
+        missile_ttl: Option<f64>,
+        missile_interception: bool,
+        missile_radius: Option<f64>,
+        missiles_visible: bool,
+        sensor_distance_std: f64,
+        sensor_dropout: f64,
+        sensor_misclassification: f64,
+        radar_radius: Option<f64>,
+        radar_ids: bool,
+        radar_line_of_sight: bool,
+        spawn_min_distance: f64,
+        avoid_firing_lines: bool,
+        hp: Option<f64>,
+        shield_capacity: Option<f64>,
+        shield_regeneration: f64,
+        missile_damage: Option<f64>,
+        friendly_fire: bool,
+        time_limit: Option<f64>,
+        tiebreak: &str,
+    ) -> PyResult<Self> {
+        let mut config = _GameConfig {
+            seed,
+            friendly_fire,
+            time_limit,
+            missile_range,
+            missile_ttl,
+            missile_interception,
+            missiles_visible,
+            ..Default::default()
+        };
+        if let Some(r) = missile_radius {
+            config.missile_radius = r;
+        }
+        let noise = _SensorNoise {
+            distance_std: sensor_distance_std,
+            dropout: sensor_dropout,
+            misclassification: sensor_misclassification,
+        };
+        if noise != _SensorNoise::default() {
+            config.sensor_noise = Some(noise);
+        }
+        config.radar = radar_radius.map(|radius| _RadarConfig {
+            radius,
+            show_ids: radar_ids,
+            line_of_sight: radar_line_of_sight,
+        });
+        config.spawn = _SpawnConfig {
+            min_distance: spawn_min_distance,
+            avoid_firing_lines,
+        };
+        if let Some(hp) = hp {
+            config.player_hp = hp;
+        }
+        if let Some(d) = missile_damage {
+            config.missile_damage = d;
+        }
+        config.shield = shield_capacity.map(|capacity| _ShieldConfig {
+            capacity,
+            regeneration: shield_regeneration,
+        });
+        if let Some(t) = time_step {
+            config.time_step = t;
+        }
+        if let Some(n) = max_substeps {
+            config.max_substeps = n;
+        }
+        config.flight_model = match flight_model {
+            "arcade" => _FlightModel::Arcade,
+            "newtonian" => {
+                let mut newtonian = _NewtonianConfig::default();
+                if let Some(a) = acceleration {
+                    newtonian.acceleration = a;
+                }
+                if let Some(w) = max_angular_velocity {
+                    newtonian.max_angular_velocity = w;
+                }
+                if let Some(d) = drag {
+                    newtonian.drag = d;
+                }
+                _FlightModel::Newtonian(newtonian)
+            }
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown flight model: {}",
+                    flight_model
+                )))
+            }
+        };
+        config.weapon.cooldown = cooldown;
+        config.weapon.ammo = ammo;
+        config.weapon.energy = energy_capacity.map(|capacity| _EnergyConfig {
+            capacity,
+            regeneration: energy_regeneration,
+            shot_cost,
+        });
+        config.tiebreak = match tiebreak {
+            "kills" => _Tiebreak::Kills,
+            "damage" => _Tiebreak::Damage,
+            "draw" => _Tiebreak::Draw,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown tiebreak: {}",
+                    tiebreak
+                )))
+            }
+        };
+        config
+            .validate()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Game(_Game::new(map.0.clone(), config)))
     }
 
     pub fn register_player(&self, player: &Player) {
@@ -30,6 +198,30 @@ impl Game {
         self.0.process(time);
     }
 
+    pub fn spawn_player(&self, player: &Player) -> PyResult<()> {
+        self.0
+            .spawn_player(&player.0)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    pub fn get_free_point(&self, r: f64) -> PyResult<(f64, f64)> {
+        self.0
+            .lock()
+            .unwrap()
+            .get_free_point(r)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[getter]
+    pub fn seed(&self) -> u64 {
+        self.0.lock().unwrap().seed
+    }
+
+    #[getter]
+    fn time(&self) -> f64 {
+        self.0.lock().unwrap().time
+    }
+
     fn get_missiles(&self) -> Vec<(f64, f64)> {
         self.0
             .lock()
@@ -42,6 +234,36 @@ impl Game {
             .collect()
     }
 
+    fn drain_events<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.0
+            .lock()
+            .unwrap()
+            .drain_events()
+            .into_iter()
+            .map(|e| event_to_dict(py, e))
+            .collect()
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot(self.0.lock().unwrap().snapshot())
+    }
+
+    fn restore(&self, snapshot: &Snapshot) -> PyResult<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .restore(&snapshot.0)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[getter]
+    pub fn winner_team(&self) -> Option<usize> {
+        match self.0.lock().unwrap().status {
+            _GameStatus::Over(team) => Some(team),
+            _ => None,
+        }
+    }
+
     #[getter]
     pub fn status(&self) -> &str {
         match self.0.lock().unwrap().status {
@@ -51,3 +273,106 @@ impl Game {
         }
     }
 }
+
+fn event_to_dict(py: Python<'_>, game_event: _GameEvent) -> PyResult<Bound<'_, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("time", game_event.time)?;
+    let name = match game_event.event {
+        _Event::ShotFired {
+            missile_id,
+            player_id,
+            x,
+            y,
+            direction,
+        } => {
+            dict.set_item("missile_id", missile_id)?;
+            dict.set_item("player_id", player_id)?;
+            dict.set_item("x", x)?;
+            dict.set_item("y", y)?;
+            dict.set_item("direction", direction)?;
+            "shot_fired"
+        }
+        _Event::MissileExpired { missile_id, x, y } => {
+            dict.set_item("missile_id", missile_id)?;
+            dict.set_item("x", x)?;
+            dict.set_item("y", y)?;
+            "missile_expired"
+        }
+        _Event::MissileHitBorder { missile_id, x, y } => {
+            dict.set_item("missile_id", missile_id)?;
+            dict.set_item("x", x)?;
+            dict.set_item("y", y)?;
+            "missile_hit_border"
+        }
+        _Event::MissileHitBarrier {
+            missile_id,
+            barrier,
+            x,
+            y,
+        } => {
+            dict.set_item("missile_id", missile_id)?;
+            dict.set_item("barrier", barrier)?;
+            dict.set_item("x", x)?;
+            dict.set_item("y", y)?;
+            "missile_hit_barrier"
+        }
+        _Event::MissileIntercepted {
+            missile_id,
+            other_id,
+            x,
+            y,
+        } => {
+            dict.set_item("missile_id", missile_id)?;
+            dict.set_item("other_id", other_id)?;
+            dict.set_item("x", x)?;
+            dict.set_item("y", y)?;
+            "missile_intercepted"
+        }
+        _Event::PlayerHit {
+            missile_id,
+            attacker_id,
+            player_id,
+            damage,
+            x,
+            y,
+        } => {
+            dict.set_item("missile_id", missile_id)?;
+            dict.set_item("attacker_id", attacker_id)?;
+            dict.set_item("player_id", player_id)?;
+            dict.set_item("damage", damage)?;
+            dict.set_item("x", x)?;
+            dict.set_item("y", y)?;
+            "player_hit"
+        }
+        _Event::PlayerKilled {
+            player_id,
+            killer_id,
+            x,
+            y,
+        } => {
+            dict.set_item("player_id", player_id)?;
+            dict.set_item("killer_id", killer_id)?;
+            dict.set_item("x", x)?;
+            dict.set_item("y", y)?;
+            "player_killed"
+        }
+        _Event::ShipCollision {
+            player_id,
+            other_id,
+            x,
+            y,
+        } => {
+            dict.set_item("player_id", player_id)?;
+            dict.set_item("other_id", other_id)?;
+            dict.set_item("x", x)?;
+            dict.set_item("y", y)?;
+            "ship_collision"
+        }
+        _Event::GameOver { winner_team } => {
+            dict.set_item("winner_team", winner_team)?;
+            "game_over"
+        }
+    };
+    dict.set_item("event", name)?;
+    Ok(dict)
+}
@@ -19,6 +19,7 @@ fn space_drive_game(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add("__version__", get_version())?;
     m.add("__authors__", get_authors())?;
     m.add_class::<game::Game>()?;
+    m.add_class::<game::Snapshot>()?;
     m.add_class::<map::Map>()?;
     m.add_class::<player::Player>()?;
     Ok(())
@@ -1,6 +1,18 @@
+// `#[pymethods]` of pyo3 0.21 puts impls of methods with `#[pyo3(signature = ...)]` into a const
+// block next to the item, newer toolchains flag it with `non_local_definitions`
+#![allow(non_local_definitions)]
+
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
-use space_drive_game_core::map::Map as _Map;
+#[rustfmt::skip]
+use space_drive_game_core::map::{
+    Map             as _Map,
+    Symmetry        as _Symmetry,
+};
 
 #[pyclass]
 pub struct Map(pub _Map);
@@ -8,36 +20,102 @@ pub struct Map(pub _Map);
 #[pymethods]
 impl Map {
     #[new]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (width, height, barriers_amount, max_barrier_radius, seed = None, symmetry = None, players = 2, min_gap = None))]
     pub fn new(
         width: f64,
         height: f64,
-        barriers_amount: u8,
+        barriers_amount: usize,
         max_barrier_radius: f64,
         seed: Option<u64>,
+        symmetry: Option<&str>,
+        players: usize,
+        min_gap: Option<f64>,
     ) -> PyResult<Self> {
-        match seed {
-            Some(s) => Ok(Map(_Map::new(
+        let seed = seed.unwrap_or_else(rand::random::<u64>);
+        let symmetry = match symmetry {
+            None => None,
+            Some("mirror") => Some(_Symmetry::Mirror),
+            Some("rotational") => Some(_Symmetry::Rotational),
+            Some(symmetry) => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown symmetry: {}",
+                    symmetry
+                )))
+            }
+        };
+        Ok(Map(match (symmetry, min_gap) {
+            (Some(_), Some(_)) => {
+                return Err(PyValueError::new_err(
+                    "min_gap isn't supported by symmetric maps",
+                ))
+            }
+            (Some(symmetry), None) => _Map::new_symmetric(
                 width,
                 height,
                 barriers_amount,
                 max_barrier_radius,
-                s,
-            ))),
-            None => Ok(Map(_Map::new_without_seed(
+                seed,
+                symmetry,
+                players,
+            ),
+            (None, Some(min_gap)) => _Map::new_with_gaps(
                 width,
                 height,
                 barriers_amount,
                 max_barrier_radius,
-            ))),
-        }
+                seed,
+                min_gap,
+            ),
+            (None, None) => _Map::new(width, height, barriers_amount, max_barrier_radius, seed),
+        }))
     }
 
     pub fn get_barriers(&self) -> Vec<(f64, f64, f64)> {
-        self.0.barriers.iter().map(|b| (b.x, b.y, b.r)).collect()
+        self.0
+            .barriers
+            .iter()
+            .map(|b| (b.x, b.y, b.bounding_radius()))
+            .collect()
+    }
+
+    pub fn get_spawn_points(&self) -> Vec<(f64, f64, f64)> {
+        self.0
+            .spawn_points
+            .iter()
+            .map(|s| (s.x, s.y, s.direction))
+            .collect()
+    }
+
+    fn validate<'py>(&self, py: Python<'py>, ship_radius: f64) -> PyResult<Bound<'py, PyDict>> {
+        let report = self.0.validate(ship_radius);
+        let dict = PyDict::new_bound(py);
+        dict.set_item("valid", report.is_valid())?;
+        dict.set_item("overlaps", report.overlaps)?;
+        dict.set_item("narrow_gaps", report.narrow_gaps)?;
+        dict.set_item("regions", report.regions)?;
+        dict.set_item("unreachable_area", report.unreachable_area)?;
+        dict.set_item("bad_spawn_points", report.bad_spawn_points)?;
+        Ok(dict)
+    }
+
+    pub fn repair(&mut self, ship_radius: f64) -> Vec<(f64, f64, f64)> {
+        self.0
+            .repair(ship_radius)
+            .into_iter()
+            .map(|b| (b.x, b.y, b.bounding_radius()))
+            .collect()
     }
 
-    pub fn get_free_point(&self, r: f64) -> (f64, f64) {
-        self.0.get_free_point(r)
+    #[pyo3(signature = (r, seed = None))]
+    pub fn get_free_point(&self, r: f64, seed: Option<u64>) -> PyResult<(f64, f64)> {
+        let mut rng = match seed {
+            Some(s) => StdRng::seed_from_u64(s),
+            None => StdRng::from_entropy(),
+        };
+        self.0
+            .get_free_point(r, &mut rng)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
     #[getter]
@@ -1,11 +1,23 @@
+// `#[pymethods]` of pyo3 0.21 puts impls of methods with `#[pyo3(signature = ...)]` into a const
+// block next to the item, newer toolchains flag it with `non_local_definitions`
+#![allow(non_local_definitions)]
+
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 use std::sync::{Arc, Mutex};
 
+#[rustfmt::skip]
+use space_drive_game_core::sensors::{
+    RadarContact    as _RadarContact,
+    RadarTarget     as _RadarTarget,
+    SensorNoise     as _SensorNoise,
+};
 #[rustfmt::skip]
 use space_drive_game_core::player::{
     Player          as _Player,
     PlayerTrait     as _PlayerTrait,
     PlayerStatus    as _PlayerStatus,
+    RayObservation  as _RayObservation,
     ViewHit         as _ViewHit,
     ViewTrait       as _ViewTrait,
 };
@@ -59,6 +71,28 @@ impl Player {
         self.0.set_speed(speed);
     }
 
+    pub fn set_thrust(&mut self, thrust: f64) {
+        self.0.set_thrust(thrust);
+    }
+
+    pub fn set_turn(&mut self, turn: f64) {
+        self.0.set_turn(turn);
+    }
+
+    pub fn set_team(&mut self, team: usize) {
+        self.0.set_team(team);
+    }
+
+    #[getter]
+    pub fn id(&self) -> usize {
+        self.0.get_id()
+    }
+
+    #[getter]
+    pub fn team(&self) -> usize {
+        self.0.get_team()
+    }
+
     #[getter]
     pub fn direction(&self) -> f64 {
         self.0.get_direction()
@@ -69,6 +103,11 @@ impl Player {
         self.0.get_speed()
     }
 
+    #[getter]
+    pub fn velocity(&self) -> (f64, f64) {
+        self.0.get_velocity()
+    }
+
     #[getter]
     pub fn x(&self) -> f64 {
         self.0.get_x()
@@ -83,24 +122,132 @@ impl Player {
     pub fn status(&self) -> &str {
         match self.0.lock().unwrap().status {
             _PlayerStatus::Win => "[WIN]",
+            _PlayerStatus::Lose => "[LOSE]",
+            _PlayerStatus::Draw => "[DRAW]",
             _PlayerStatus::InGame => "[INGAME]",
             _PlayerStatus::KilledBy(_) => "[DEAD]",
         }
     }
 
-    pub fn view(&self) -> Vec<(&str, f64)> {
+    #[pyo3(signature = (distance_std = 0.0, dropout = 0.0, misclassification = 0.0))]
+    pub fn set_sensor_noise(&mut self, distance_std: f64, dropout: f64, misclassification: f64) {
+        self.0.lock().unwrap().sensor_noise = Some(_SensorNoise {
+            distance_std,
+            dropout,
+            misclassification,
+        });
+    }
+
+    pub fn view(&self) -> Vec<(&str, Option<f64>)> {
+        self.0.view().into_iter().map(view_hit_to_tuple).collect()
+    }
+
+    fn view_extended<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
         self.0
-            .view()
+            .view_extended()
             .into_iter()
-            .map(|view_hit| match view_hit {
-                _ViewHit::Border(distance) => ("[BORDER]", distance),
-                _ViewHit::Barrier(distance) => ("[BARRIER]", distance),
-                _ViewHit::Enemy(distance) => ("[ENEMY]", distance),
-            })
+            .map(|o| observation_to_dict(py, o))
             .collect()
     }
 
-    pub fn fire(&self) {
-        self.0.fire()
+    fn radar<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.0
+            .radar()
+            .into_iter()
+            .map(|c| radar_contact_to_dict(py, c))
+            .collect()
     }
+
+    pub fn fire(&mut self) -> bool {
+        self.0.fire().is_ok()
+    }
+
+    #[getter]
+    pub fn cooldown(&self) -> f64 {
+        self.0.lock().unwrap().weapon.cooldown
+    }
+
+    #[getter]
+    pub fn ammo(&self) -> Option<u32> {
+        self.0.lock().unwrap().weapon.ammo
+    }
+
+    #[getter]
+    pub fn energy(&self) -> f64 {
+        self.0.lock().unwrap().weapon.energy
+    }
+
+    #[getter]
+    pub fn hp(&self) -> f64 {
+        self.0.lock().unwrap().health.hp
+    }
+
+    #[getter]
+    pub fn shield(&self) -> f64 {
+        self.0.lock().unwrap().health.shield
+    }
+
+    #[getter]
+    pub fn damage_dealt(&self) -> f64 {
+        self.0.lock().unwrap().damage_dealt
+    }
+
+    #[getter]
+    pub fn kills(&self) -> usize {
+        self.0.lock().unwrap().kills
+    }
+}
+
+fn view_hit_to_tuple(view_hit: _ViewHit) -> (&'static str, Option<f64>) {
+    match view_hit {
+        _ViewHit::Border(distance) => ("[BORDER]", Some(distance)),
+        _ViewHit::Barrier(distance) => ("[BARRIER]", Some(distance)),
+        _ViewHit::Enemy(distance) => ("[ENEMY]", Some(distance)),
+        _ViewHit::Ally(distance) => ("[ALLY]", Some(distance)),
+        _ViewHit::Missile { distance, .. } => ("[MISSILE]", Some(distance)),
+        _ViewHit::Unknown => ("[UNKNOWN]", None),
+    }
+}
+
+fn observation_to_dict(
+    py: Python<'_>,
+    observation: _RayObservation,
+) -> PyResult<Bound<'_, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    let (object, distance) = view_hit_to_tuple(observation.hit);
+    dict.set_item("object", object)?;
+    dict.set_item("distance", distance)?;
+    dict.set_item("angle", observation.angle)?;
+    dict.set_item("relative_angle", observation.relative_angle)?;
+    dict.set_item("id", observation.id)?;
+    dict.set_item("normal", observation.normal)?;
+    if let Some(ship) = observation.ship {
+        dict.set_item("velocity", ship.velocity)?;
+        dict.set_item("heading", ship.direction)?;
+    }
+    if let _ViewHit::Missile {
+        player_id,
+        direction,
+        ..
+    } = observation.hit
+    {
+        dict.set_item("owner", player_id)?;
+        dict.set_item("heading", direction)?;
+    }
+    Ok(dict)
+}
+
+fn radar_contact_to_dict(py: Python<'_>, contact: _RadarContact) -> PyResult<Bound<'_, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    let object = match contact.target {
+        _RadarTarget::Enemy => "[ENEMY]",
+        _RadarTarget::Ally => "[ALLY]",
+        _RadarTarget::Missile => "[MISSILE]",
+    };
+    dict.set_item("object", object)?;
+    dict.set_item("bearing", contact.bearing)?;
+    dict.set_item("relative_bearing", contact.relative_bearing)?;
+    dict.set_item("distance", contact.distance)?;
+    dict.set_item("id", contact.id)?;
+    Ok(dict)
 }
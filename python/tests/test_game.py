@@ -1,3 +1,4 @@
+import pytest
 from space_drive_game import Game, Map, Player
 
 
@@ -135,3 +136,168 @@ def test_missiles_borders_collision(empty_map: Map):
     # Check if the missiles were destroyed after the collision
     missiles = game.get_missiles()
     assert len(missiles) == 0
+
+
+def test_time_is_accumulated(empty_map: Map):
+    p = Player(x=1, y=1, r=0.5, max_speed=1, direction=0)
+    game = Game(empty_map, time_step=0.3)
+    game.register_player(p)
+    game.register_player(get_stub_player())
+    p.set_speed(1)
+
+    game.process(0.12345)
+    game.process(1.0)
+
+    assert round(game.time, 6) == 1.12345
+    assert round(p.y, 6) == 2.12345
+
+
+def test_newtonian_flight_model(empty_map: Map):
+    p = Player(x=50, y=10, r=1, max_speed=20, direction=0)
+    game = Game(empty_map, flight_model='newtonian', acceleration=10, drag=0)
+    game.register_player(p)
+    game.register_player(get_stub_player())
+
+    p.set_thrust(1)
+    game.process(1.0)
+    assert round(p.speed, 6) == 10.0
+    assert round(p.y, 6) == 15.5
+
+    p.set_thrust(0)
+    game.process(1.0)
+    assert round(p.velocity[1], 6) == 10.0
+    assert round(p.y, 6) == 25.5
+
+
+def test_weapon_limits(empty_map: Map):
+    p = Player(x=50, y=50, r=1, max_speed=1, direction=0)
+    assert not p.fire()
+
+    game = Game(empty_map, cooldown=0.5, ammo=2)
+    game.register_player(p)
+    game.register_player(get_stub_player())
+
+    assert p.fire()
+    assert not p.fire()
+    assert p.cooldown == 0.5
+    game.process(0.5)
+    assert p.fire()
+    assert p.ammo == 0
+    game.process(0.5)
+    assert not p.fire()
+
+
+def test_missile_range(empty_map: Map):
+    p = Player(x=50, y=10, r=1, max_speed=1, missile_speed=1, direction=0)
+    game = Game(empty_map, missile_range=5)
+    game.register_player(p)
+    game.register_player(get_stub_player())
+
+    p.fire()
+    game.process(4.0)
+    assert len(game.get_missiles()) == 1
+    game.process(1.1)
+    assert len(game.get_missiles()) == 0
+
+
+def test_hit_points(empty_map: Map):
+    p = Player(x=50, y=10, r=1, max_speed=1, missile_speed=1000, direction=0)
+    target = Player(x=50, y=60, r=1, max_speed=1)
+    game = Game(empty_map, hp=2, missile_damage=1)
+    game.register_player(p)
+    game.register_player(target)
+
+    p.fire()
+    game.process(0.1)
+    assert target.status == '[INGAME]'
+    assert target.hp == 1
+
+    p.fire()
+    game.process(0.1)
+    assert target.status == '[DEAD]'
+    assert p.damage_dealt == 2
+    assert p.kills == 1
+
+
+def test_teams(empty_map: Map):
+    players = [
+        Player(x=50, y=10, r=1, max_speed=1, missile_speed=1000, direction=0),
+        Player(x=50, y=50, r=1, max_speed=1),
+        Player(x=50, y=90, r=1, max_speed=1),
+    ]
+    for p, team in zip(players, [1, 1, 2]):
+        p.set_team(team)
+    game = Game(empty_map)
+    for p in players:
+        game.register_player(p)
+
+    players[0].fire()
+    game.process(0.2)
+
+    assert game.status == '[OVER]'
+    assert game.winner_team == 1
+    assert [p.status for p in players] == ['[WIN]', '[WIN]', '[DEAD]']
+
+
+def test_time_limit(empty_map: Map):
+    p = Player(x=1, y=1, r=0.5, max_speed=1, direction=0)
+    stub_p = get_stub_player()
+    game = Game(empty_map, time_limit=1.0)
+    game.register_player(p)
+    game.register_player(stub_p)
+
+    game.process(10.0)
+
+    assert game.status == '[OVER]'
+    assert round(game.time, 6) == 1.0
+    assert p.status == '[DRAW]'
+    assert stub_p.status == '[DRAW]'
+
+
+def test_events(empty_map: Map):
+    p = Player(x=50, y=10, r=1, max_speed=1, missile_speed=1000, direction=0)
+    target = Player(x=50, y=60, r=1, max_speed=1)
+    game = Game(empty_map)
+    game.register_player(p)
+    game.register_player(target)
+
+    p.fire()
+    game.process(0.2)
+
+    events = game.drain_events()
+    assert [e['event'] for e in events] == ['shot_fired', 'player_hit', 'player_killed', 'game_over']
+    assert events[2]['killer_id'] == events[0]['player_id']
+    assert game.drain_events() == []
+
+
+def test_snapshot(empty_map: Map):
+    p = Player(x=1, y=1, r=1, max_speed=1, direction=0)
+    game = Game(empty_map)
+    game.register_player(p)
+    game.register_player(get_stub_player())
+    p.set_speed(1)
+
+    snapshot = game.snapshot()
+    game.process(1.0)
+    game.restore(snapshot)
+
+    assert game.time == 0.0
+    assert (p.x, p.y) == (1, 1)
+
+
+def test_spawn_player(empty_map: Map):
+    game = Game(empty_map, spawn_min_distance=100, avoid_firing_lines=True)
+    p1 = Player(x=0, y=0, r=10)
+    p2 = Player(x=0, y=0, r=10)
+    game.spawn_player(p1)
+    game.spawn_player(p2)
+    assert ((p1.x - p2.x) ** 2 + (p1.y - p2.y) ** 2) ** 0.5 >= 120
+
+    with pytest.raises(ValueError):
+        game.spawn_player(Player(x=0, y=0, r=10000))
+
+
+@pytest.mark.parametrize("options", [{"time_step": 0}, {"time_step": -0.1}, {"max_substeps": 0}])
+def test_invalid_config(empty_map: Map, options: dict):
+    with pytest.raises(ValueError):
+        Game(empty_map, **options)
@@ -16,3 +16,26 @@ def test_generation_with_seed(width: int, height: int, barriers_amount: int, max
 
     assert map1.seed == map2.seed
     assert map1.get_barriers() == map2.get_barriers()
+
+
+def test_symmetric_generation(width: int, barriers_amount: int, max_barrier_radius: int):
+    m = Map(width, width, barriers_amount, max_barrier_radius, symmetry='rotational', players=4)
+    assert len(m.get_spawn_points()) == 4
+    for x, y, r in m.get_barriers():
+        # A quarter turn around the center maps the map onto itself
+        assert any(
+            abs(x2 - (width - y)) < 1e-6 and abs(y2 - x) < 1e-6 and r2 == r
+            for x2, y2, r2 in m.get_barriers()
+        )
+
+    m = Map(width, width, barriers_amount, max_barrier_radius, symmetry='mirror')
+    assert [(x, d) for x, _, d in m.get_spawn_points()] == [(width / 4, 90.0), (width * 3 / 4, 270.0)]
+
+
+def test_validate(width: int, height: int, max_barrier_radius: int):
+    m = Map(width, height, 100, max_barrier_radius, min_gap=20)
+    report = m.validate(5)
+    assert report['valid']
+    assert report['regions'] == 1
+    assert report['overlaps'] == []
+    assert m.repair(5) == []
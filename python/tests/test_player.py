@@ -30,3 +30,54 @@ def test_view(empty_map: Map, width: int, height: int):
     assert p.view() == [('[BORDER]', 40.0)]
     p.rotate(90)
     assert p.view() == [('[ENEMY]', 30.0)]
+
+
+def test_view_extended(empty_map: Map, width: int, height: int):
+    game = Game(empty_map)
+    p = Player(x=width-50, y=height-50, r=10, max_speed=1, view_angle=60, rays_amount=1, direction=90)
+    p2 = Player(x=width, y=height-50, r=10, max_speed=1, view_angle=60, rays_amount=1)
+    game.register_player(p)
+    game.register_player(p2)
+
+    [ray] = p.view_extended()
+    assert (ray['object'], ray['distance']) == ('[ENEMY]', 30.0)
+    assert ray['id'] == p2.id
+    assert ray['normal'] == (-1.0, 0.0)
+    assert ray['heading'] == p2.direction
+
+
+def test_sensor_noise(empty_map: Map):
+    game = Game(empty_map, sensor_dropout=1.0)
+    p = Player(x=50, y=50, r=10, rays_amount=1, direction=0)
+    game.register_player(p)
+    assert p.view() == [('[UNKNOWN]', None)]
+
+    p.set_sensor_noise()
+    assert p.view() == [('[BORDER]', 40.0)]
+
+
+def test_radar(empty_map: Map):
+    game = Game(empty_map, radar_radius=100)
+    p = Player(x=50, y=50, r=10, direction=0)
+    enemy = Player(x=50, y=100, r=10, direction=0)
+    far = Player(x=500, y=500, r=10, direction=0)
+    game.register_player(p)
+    game.register_player(enemy)
+    game.register_player(far)
+    assert p.radar() == [
+        {'object': '[ENEMY]', 'bearing': 0.0, 'relative_bearing': 0.0, 'distance': 50.0, 'id': enemy.id},
+    ]
+
+
+def test_view_missiles(empty_map: Map):
+    game = Game(empty_map, missiles_visible=True)
+    p = Player(x=50, y=10, r=1, rays_amount=1, direction=0)
+    enemy = Player(x=50, y=90, r=1, rays_amount=1, direction=180)
+    game.register_player(p)
+    game.register_player(enemy)
+    enemy.fire()
+
+    assert p.view() == [('[MISSILE]', 77.0)]
+    [ray] = p.view_extended()
+    assert ray['owner'] == enemy.id
+    assert ray['heading'] == 180.0
@@ -1,18 +1,143 @@
 use serde::Deserialize;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
+use crate::Error;
+use space_drive_game_core::ray_casting::RayCastBackend;
+use space_drive_game_core::{
+    CollisionResponse, EnergyConfig, FlightModel, GameConfig, NewtonianConfig, RadarConfig,
+    SensorNoise, ShieldConfig, SpawnConfig, Symmetry, Tiebreak, WeaponConfig,
+};
+
 const DEFAULT_MAP_WIDTH: f64 = 960.0;
 const DEFAULT_MAP_HEIGHT: f64 = 540.0;
-const DEFAULT_MAP_BARRIERS_AMOUNT: u8 = 30;
+const DEFAULT_MAP_BARRIERS_AMOUNT: usize = 30;
 const DEFAULT_MAP_MAX_BARRIER_RADIUS: f64 = 40.0;
 const DEFAULT_MAP_SEED: Option<u64> = None;
+const DEFAULT_MAP_FILE: Option<String> = None;
+const DEFAULT_MAP_SYMMETRY: MapSymmetryConfig = MapSymmetryConfig::None;
+const DEFAULT_MAP_MIN_GAP: Option<f64> = None;
+const DEFAULT_MAP_REPAIR: bool = false;
+const DEFAULT_SPAWN_MIN_DISTANCE: f64 = 0.0;
+const DEFAULT_SPAWN_AVOID_FIRING_LINES: bool = false;
+const DEFAULT_GAME_SEED: Option<u64> = None;
 const DEFAULT_PLAYER_RADIUS: f64 = 10.0;
 const DEFAULT_PLAYER_MAX_SPEED: f64 = 960.0;
 const DEFAULT_PLAYER_VIEW_ANGLE: f64 = 30.0;
 const DEFAULT_PLAYER_RAYS_AMOUNT: u16 = 21;
+const DEFAULT_PLAYER_EXTENDED_VIEW: bool = false;
+const DEFAULT_PLAYER_SELF_STATE: bool = false;
 const DEFAULT_PLAYER_MISSILE_SPEED: f64 = 2880.0;
+const DEFAULT_PLAYER_HP: f64 = 1.0;
+const DEFAULT_PLAYER_SHIELD_CAPACITY: Option<f64> = None;
+const DEFAULT_PLAYER_SHIELD_REGENERATION: f64 = 1.0;
+const DEFAULT_MISSILE_DAMAGE: f64 = 1.0;
+const DEFAULT_MISSILE_RANGE: Option<f64> = None;
+const DEFAULT_MISSILE_TTL: Option<f64> = None;
+const DEFAULT_MISSILE_INTERCEPTION: bool = false;
+const DEFAULT_MISSILE_RADIUS: f64 = 2.0;
+const DEFAULT_MISSILES_VISIBLE: bool = false;
+const DEFAULT_SENSOR_DISTANCE_STD: f64 = 0.0;
+const DEFAULT_SENSOR_DROPOUT: f64 = 0.0;
+const DEFAULT_SENSOR_MISCLASSIFICATION: f64 = 0.0;
+const DEFAULT_RADAR_RADIUS: Option<f64> = None;
+const DEFAULT_RADAR_IDS: bool = true;
+const DEFAULT_RADAR_LINE_OF_SIGHT: bool = false;
 const DEFAULT_PLAYERS_AMOUNT: usize = 2;
+const DEFAULT_TEAMS_AMOUNT: Option<usize> = None;
+const DEFAULT_FRIENDLY_FIRE: bool = false;
+const DEFAULT_TIME_LIMIT: Option<f64> = None;
+const DEFAULT_TIEBREAK: TiebreakConfig = TiebreakConfig::Draw;
 const DEFAULT_HISTORY_OPTIMIZATION_RATE: u8 = 30;
+const DEFAULT_TIME_STEP: f64 = 0.1;
+const DEFAULT_MAX_SUBSTEPS: usize = 10000;
+const DEFAULT_RAY_CAST_BACKEND: RayCastBackendConfig = RayCastBackendConfig::Analytic;
+const DEFAULT_COLLISION_RESPONSE: CollisionResponseConfig = CollisionResponseConfig::Block;
+const DEFAULT_FLIGHT_MODEL: FlightModelConfig = FlightModelConfig::Arcade;
+const DEFAULT_PLAYER_ACCELERATION: f64 = 1920.0;
+const DEFAULT_PLAYER_MAX_ANGULAR_VELOCITY: f64 = 360.0;
+const DEFAULT_PLAYER_DRAG: f64 = 0.5;
+const DEFAULT_WEAPON_COOLDOWN: f64 = 0.0;
+const DEFAULT_WEAPON_AMMO: Option<u32> = None;
+const DEFAULT_WEAPON_ENERGY_CAPACITY: Option<f64> = None;
+const DEFAULT_WEAPON_ENERGY_REGENERATION: f64 = 1.0;
+const DEFAULT_WEAPON_SHOT_COST: f64 = 1.0;
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionResponseConfig {
+    Block,
+    Bounce,
+    Ram,
+}
+
+impl From<CollisionResponseConfig> for CollisionResponse {
+    fn from(value: CollisionResponseConfig) -> Self {
+        match value {
+            CollisionResponseConfig::Block => CollisionResponse::Block,
+            CollisionResponseConfig::Bounce => CollisionResponse::Bounce,
+            CollisionResponseConfig::Ram => CollisionResponse::Ram,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum RayCastBackendConfig {
+    Analytic,
+    SphereTracing,
+}
+
+impl From<RayCastBackendConfig> for RayCastBackend {
+    fn from(value: RayCastBackendConfig) -> Self {
+        match value {
+            RayCastBackendConfig::Analytic => RayCastBackend::Analytic,
+            RayCastBackendConfig::SphereTracing => RayCastBackend::SphereTracing,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum FlightModelConfig {
+    Arcade,
+    Newtonian,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum TiebreakConfig {
+    Kills,
+    Damage,
+    Draw,
+}
+
+impl From<TiebreakConfig> for Tiebreak {
+    fn from(value: TiebreakConfig) -> Self {
+        match value {
+            TiebreakConfig::Kills => Tiebreak::Kills,
+            TiebreakConfig::Damage => Tiebreak::Damage,
+            TiebreakConfig::Draw => Tiebreak::Draw,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum MapSymmetryConfig {
+    None,
+    Mirror,
+    Rotational,
+}
+
+impl From<MapSymmetryConfig> for Option<Symmetry> {
+    fn from(value: MapSymmetryConfig) -> Self {
+        match value {
+            MapSymmetryConfig::None => None,
+            MapSymmetryConfig::Mirror => Some(Symmetry::Mirror),
+            MapSymmetryConfig::Rotational => Some(Symmetry::Rotational),
+        }
+    }
+}
 
 #[derive(Deserialize)]
 pub struct Config {
@@ -25,11 +150,25 @@ pub struct Config {
     #[serde(default = "default_map_height")]
     pub map_height: f64,
     #[serde(default = "default_map_barriers_amount")]
-    pub map_barriers_amount: u8,
+    pub map_barriers_amount: usize,
     #[serde(default = "default_map_max_barrier_radius")]
     pub map_max_barrier_radius: f64,
     #[serde(default = "default_map_seed")]
     pub map_seed: Option<u64>,
+    #[serde(default = "default_map_file")]
+    pub map_file: Option<String>,
+    #[serde(default = "default_map_symmetry")]
+    pub map_symmetry: MapSymmetryConfig,
+    #[serde(default = "default_map_min_gap")]
+    pub map_min_gap: Option<f64>,
+    #[serde(default = "default_map_repair")]
+    pub map_repair: bool,
+    #[serde(default = "default_spawn_min_distance")]
+    pub spawn_min_distance: f64,
+    #[serde(default = "default_spawn_avoid_firing_lines")]
+    pub spawn_avoid_firing_lines: bool,
+    #[serde(default = "default_game_seed")]
+    pub game_seed: Option<u64>,
     #[serde(default = "default_player_radius")]
     pub player_radius: f64,
     #[serde(default = "default_player_max_speed")]
@@ -38,12 +177,80 @@ pub struct Config {
     pub player_view_angle: f64,
     #[serde(default = "default_player_rays_amount")]
     pub player_rays_amount: u16,
+    #[serde(default = "default_player_extended_view")]
+    pub player_extended_view: bool,
+    #[serde(default = "default_player_self_state")]
+    pub player_self_state: bool,
     #[serde(default = "default_player_missile_speed")]
     pub player_missile_speed: f64,
+    #[serde(default = "default_player_hp")]
+    pub player_hp: f64,
+    #[serde(default = "default_player_shield_capacity")]
+    pub player_shield_capacity: Option<f64>,
+    #[serde(default = "default_player_shield_regeneration")]
+    pub player_shield_regeneration: f64,
+    #[serde(default = "default_missile_damage")]
+    pub missile_damage: f64,
+    #[serde(default = "default_missile_range")]
+    pub missile_range: Option<f64>,
+    #[serde(default = "default_missile_ttl")]
+    pub missile_ttl: Option<f64>,
+    #[serde(default = "default_missile_interception")]
+    pub missile_interception: bool,
+    #[serde(default = "default_missile_radius")]
+    pub missile_radius: f64,
+    #[serde(default = "default_missiles_visible")]
+    pub missiles_visible: bool,
+    #[serde(default = "default_sensor_distance_std")]
+    pub sensor_distance_std: f64,
+    #[serde(default = "default_sensor_dropout")]
+    pub sensor_dropout: f64,
+    #[serde(default = "default_sensor_misclassification")]
+    pub sensor_misclassification: f64,
+    #[serde(default = "default_radar_radius")]
+    pub radar_radius: Option<f64>,
+    #[serde(default = "default_radar_ids")]
+    pub radar_ids: bool,
+    #[serde(default = "default_radar_line_of_sight")]
+    pub radar_line_of_sight: bool,
     #[serde(default = "default_players_amount")]
     pub players_amount: usize,
+    #[serde(default = "default_teams_amount")]
+    pub teams_amount: Option<usize>,
+    #[serde(default = "default_friendly_fire")]
+    pub friendly_fire: bool,
+    #[serde(default = "default_time_limit")]
+    pub time_limit: Option<f64>,
+    #[serde(default = "default_tiebreak")]
+    pub tiebreak: TiebreakConfig,
     #[serde(default = "default_history_optimization_rate")]
     pub history_optimization_rate: u8,
+    #[serde(default = "default_collision_response")]
+    pub collision_response: CollisionResponseConfig,
+    #[serde(default = "default_time_step")]
+    pub time_step: f64,
+    #[serde(default = "default_max_substeps")]
+    pub max_substeps: usize,
+    #[serde(default = "default_ray_cast_backend")]
+    pub ray_cast_backend: RayCastBackendConfig,
+    #[serde(default = "default_flight_model")]
+    pub flight_model: FlightModelConfig,
+    #[serde(default = "default_player_acceleration")]
+    pub player_acceleration: f64,
+    #[serde(default = "default_player_max_angular_velocity")]
+    pub player_max_angular_velocity: f64,
+    #[serde(default = "default_player_drag")]
+    pub player_drag: f64,
+    #[serde(default = "default_weapon_cooldown")]
+    pub weapon_cooldown: f64,
+    #[serde(default = "default_weapon_ammo")]
+    pub weapon_ammo: Option<u32>,
+    #[serde(default = "default_weapon_energy_capacity")]
+    pub weapon_energy_capacity: Option<f64>,
+    #[serde(default = "default_weapon_energy_regeneration")]
+    pub weapon_energy_regeneration: f64,
+    #[serde(default = "default_weapon_shot_cost")]
+    pub weapon_shot_cost: f64,
 }
 
 fn default_host() -> SocketAddr {
@@ -62,7 +269,7 @@ fn default_map_height() -> f64 {
     DEFAULT_MAP_HEIGHT
 }
 
-fn default_map_barriers_amount() -> u8 {
+fn default_map_barriers_amount() -> usize {
     DEFAULT_MAP_BARRIERS_AMOUNT
 }
 
@@ -74,6 +281,34 @@ fn default_map_seed() -> Option<u64> {
     DEFAULT_MAP_SEED
 }
 
+fn default_map_file() -> Option<String> {
+    DEFAULT_MAP_FILE
+}
+
+fn default_map_symmetry() -> MapSymmetryConfig {
+    DEFAULT_MAP_SYMMETRY
+}
+
+fn default_map_min_gap() -> Option<f64> {
+    DEFAULT_MAP_MIN_GAP
+}
+
+fn default_map_repair() -> bool {
+    DEFAULT_MAP_REPAIR
+}
+
+fn default_spawn_min_distance() -> f64 {
+    DEFAULT_SPAWN_MIN_DISTANCE
+}
+
+fn default_spawn_avoid_firing_lines() -> bool {
+    DEFAULT_SPAWN_AVOID_FIRING_LINES
+}
+
+fn default_game_seed() -> Option<u64> {
+    DEFAULT_GAME_SEED
+}
+
 fn default_player_radius() -> f64 {
     DEFAULT_PLAYER_RADIUS
 }
@@ -90,20 +325,226 @@ fn default_player_rays_amount() -> u16 {
     DEFAULT_PLAYER_RAYS_AMOUNT
 }
 
+fn default_player_extended_view() -> bool {
+    DEFAULT_PLAYER_EXTENDED_VIEW
+}
+
+fn default_player_self_state() -> bool {
+    DEFAULT_PLAYER_SELF_STATE
+}
+
 fn default_player_missile_speed() -> f64 {
     DEFAULT_PLAYER_MISSILE_SPEED
 }
 
+fn default_player_hp() -> f64 {
+    DEFAULT_PLAYER_HP
+}
+
+fn default_player_shield_capacity() -> Option<f64> {
+    DEFAULT_PLAYER_SHIELD_CAPACITY
+}
+
+fn default_player_shield_regeneration() -> f64 {
+    DEFAULT_PLAYER_SHIELD_REGENERATION
+}
+
+fn default_missile_damage() -> f64 {
+    DEFAULT_MISSILE_DAMAGE
+}
+
+fn default_missile_range() -> Option<f64> {
+    DEFAULT_MISSILE_RANGE
+}
+
+fn default_missile_ttl() -> Option<f64> {
+    DEFAULT_MISSILE_TTL
+}
+
+fn default_missile_interception() -> bool {
+    DEFAULT_MISSILE_INTERCEPTION
+}
+
+fn default_missile_radius() -> f64 {
+    DEFAULT_MISSILE_RADIUS
+}
+
+fn default_missiles_visible() -> bool {
+    DEFAULT_MISSILES_VISIBLE
+}
+
+fn default_sensor_distance_std() -> f64 {
+    DEFAULT_SENSOR_DISTANCE_STD
+}
+
+fn default_sensor_dropout() -> f64 {
+    DEFAULT_SENSOR_DROPOUT
+}
+
+fn default_sensor_misclassification() -> f64 {
+    DEFAULT_SENSOR_MISCLASSIFICATION
+}
+
+fn default_radar_radius() -> Option<f64> {
+    DEFAULT_RADAR_RADIUS
+}
+
+fn default_radar_ids() -> bool {
+    DEFAULT_RADAR_IDS
+}
+
+fn default_radar_line_of_sight() -> bool {
+    DEFAULT_RADAR_LINE_OF_SIGHT
+}
+
 fn default_players_amount() -> usize {
     DEFAULT_PLAYERS_AMOUNT
 }
 
+fn default_teams_amount() -> Option<usize> {
+    DEFAULT_TEAMS_AMOUNT
+}
+
+fn default_friendly_fire() -> bool {
+    DEFAULT_FRIENDLY_FIRE
+}
+
+fn default_time_limit() -> Option<f64> {
+    DEFAULT_TIME_LIMIT
+}
+
+fn default_tiebreak() -> TiebreakConfig {
+    DEFAULT_TIEBREAK
+}
+
 fn default_history_optimization_rate() -> u8 {
     DEFAULT_HISTORY_OPTIMIZATION_RATE
 }
 
+fn default_collision_response() -> CollisionResponseConfig {
+    DEFAULT_COLLISION_RESPONSE
+}
+
+fn default_time_step() -> f64 {
+    DEFAULT_TIME_STEP
+}
+
+fn default_max_substeps() -> usize {
+    DEFAULT_MAX_SUBSTEPS
+}
+
+fn default_ray_cast_backend() -> RayCastBackendConfig {
+    DEFAULT_RAY_CAST_BACKEND
+}
+
+fn default_flight_model() -> FlightModelConfig {
+    DEFAULT_FLIGHT_MODEL
+}
+
+fn default_player_acceleration() -> f64 {
+    DEFAULT_PLAYER_ACCELERATION
+}
+
+fn default_player_max_angular_velocity() -> f64 {
+    DEFAULT_PLAYER_MAX_ANGULAR_VELOCITY
+}
+
+fn default_player_drag() -> f64 {
+    DEFAULT_PLAYER_DRAG
+}
+
+fn default_weapon_cooldown() -> f64 {
+    DEFAULT_WEAPON_COOLDOWN
+}
+
+fn default_weapon_ammo() -> Option<u32> {
+    DEFAULT_WEAPON_AMMO
+}
+
+fn default_weapon_energy_capacity() -> Option<f64> {
+    DEFAULT_WEAPON_ENERGY_CAPACITY
+}
+
+fn default_weapon_energy_regeneration() -> f64 {
+    DEFAULT_WEAPON_ENERGY_REGENERATION
+}
+
+fn default_weapon_shot_cost() -> f64 {
+    DEFAULT_WEAPON_SHOT_COST
+}
+
 impl Config {
-    pub fn new() -> Result<Config, envy::Error> {
-        envy::from_env::<Config>()
+    pub fn new() -> Result<Config, Error> {
+        let config = envy::from_env::<Config>()?;
+        config.game_config().validate()?;
+        Ok(config)
+    }
+
+    pub fn game_config(&self) -> GameConfig {
+        GameConfig {
+            time_step: self.time_step,
+            max_substeps: self.max_substeps,
+            collision_response: self.collision_response.into(),
+            friendly_fire: self.friendly_fire,
+            player_radius: self.player_radius,
+            player_max_speed: self.player_max_speed,
+            player_hp: self.player_hp,
+            shield: self.player_shield_capacity.map(|capacity| ShieldConfig {
+                capacity,
+                regeneration: self.player_shield_regeneration,
+            }),
+            missile_speed: self.player_missile_speed,
+            missile_damage: self.missile_damage,
+            missile_range: self.missile_range,
+            missile_ttl: self.missile_ttl,
+            missile_interception: self.missile_interception,
+            missile_radius: self.missile_radius,
+            missiles_visible: self.missiles_visible,
+            sensor_noise: self.sensor_noise(),
+            radar: self.radar_radius.map(|radius| RadarConfig {
+                radius,
+                show_ids: self.radar_ids,
+                line_of_sight: self.radar_line_of_sight,
+            }),
+            time_limit: self.time_limit,
+            tiebreak: self.tiebreak.into(),
+            seed: self.game_seed,
+            ray_cast_backend: self.ray_cast_backend.into(),
+            flight_model: self.flight_model(),
+            spawn: SpawnConfig {
+                min_distance: self.spawn_min_distance,
+                avoid_firing_lines: self.spawn_avoid_firing_lines,
+            },
+            weapon: WeaponConfig {
+                cooldown: self.weapon_cooldown,
+                ammo: self.weapon_ammo,
+                energy: self.weapon_energy_capacity.map(|capacity| EnergyConfig {
+                    capacity,
+                    regeneration: self.weapon_energy_regeneration,
+                    shot_cost: self.weapon_shot_cost,
+                }),
+            },
+        }
+    }
+
+    fn flight_model(&self) -> FlightModel {
+        match self.flight_model {
+            FlightModelConfig::Arcade => FlightModel::Arcade,
+            FlightModelConfig::Newtonian => FlightModel::Newtonian(NewtonianConfig {
+                acceleration: self.player_acceleration,
+                max_angular_velocity: self.player_max_angular_velocity,
+                drag: self.player_drag,
+            }),
+        }
+    }
+
+    /// Sensors are perfect unless some noise is set
+    fn sensor_noise(&self) -> Option<SensorNoise> {
+        let noise = SensorNoise {
+            distance_std: self.sensor_distance_std,
+            dropout: self.sensor_dropout,
+            misclassification: self.sensor_misclassification,
+        };
+        (noise != SensorNoise::default()).then_some(noise)
     }
 }
@@ -8,7 +8,8 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 
 use space_drive_game_core::{
-    Game, GameTrait, Player, PlayerStatus, PlayerTrait, RegisterPlayer, ViewHit, ViewTrait,
+    FireError, Game, GameTrait, Player, PlayerStatus, PlayerTrait, RadarContact, RadarTarget,
+    RayObservation, RegisterPlayer, SelfState, ViewHit, ViewTrait,
 };
 
 use crate::config::Config;
@@ -22,18 +23,168 @@ struct PlayerName {
 #[derive(Serialize)]
 struct ViewHitSchema {
     object: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    distance: Option<f64>,
+}
+
+impl From<ViewHit> for ViewHitSchema {
+    fn from(value: ViewHit) -> Self {
+        let (object, distance) = match value {
+            ViewHit::Barrier(d) => ("BARRIER", Some(d)),
+            ViewHit::Border(d) => ("BORDER", Some(d)),
+            ViewHit::Enemy(d) => ("ENEMY", Some(d)),
+            ViewHit::Ally(d) => ("ALLY", Some(d)),
+            ViewHit::Missile { distance, .. } => ("MISSILE", Some(distance)),
+            ViewHit::Unknown => ("UNKNOWN", None),
+        };
+        ViewHitSchema {
+            object: object.to_string(),
+            distance,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RaySchema {
+    #[serde(flatten)]
+    hit: ViewHitSchema,
+    angle: f64,
+    relative_angle: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<usize>,
+    normal: (f64, f64),
+    #[serde(skip_serializing_if = "Option::is_none")]
+    velocity: Option<(f64, f64)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    heading: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owner: Option<usize>,
+}
+
+impl From<RayObservation> for RaySchema {
+    fn from(value: RayObservation) -> Self {
+        let (heading, owner) = match value.hit {
+            ViewHit::Missile {
+                player_id,
+                direction,
+                ..
+            } => (Some(direction), Some(player_id)),
+            _ => (value.ship.map(|s| s.direction), None),
+        };
+        RaySchema {
+            hit: value.hit.into(),
+            angle: value.angle,
+            relative_angle: value.relative_angle,
+            id: value.id,
+            normal: value.normal,
+            velocity: value.ship.map(|s| s.velocity),
+            heading,
+            owner,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SelfStateSchema {
+    x: f64,
+    y: f64,
+    normalized_x: f64,
+    normalized_y: f64,
+    direction: f64,
+    speed: f64,
+    cooldown: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ammo: Option<u32>,
+    energy: f64,
+    hp: f64,
+    shield: f64,
+    time: f64,
+}
+
+impl From<SelfState> for SelfStateSchema {
+    fn from(value: SelfState) -> Self {
+        SelfStateSchema {
+            x: value.x,
+            y: value.y,
+            normalized_x: value.normalized_x,
+            normalized_y: value.normalized_y,
+            direction: value.direction,
+            speed: value.speed,
+            cooldown: value.cooldown,
+            ammo: value.ammo,
+            energy: value.energy,
+            hp: value.hp,
+            shield: value.shield,
+            time: value.time,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum FireErrorSchema {
+    Inactive,
+    Cooldown,
+    NoAmmo,
+    NoEnergy,
+}
+
+impl From<FireError> for FireErrorSchema {
+    fn from(value: FireError) -> Self {
+        match value {
+            FireError::Inactive => FireErrorSchema::Inactive,
+            FireError::Cooldown => FireErrorSchema::Cooldown,
+            FireError::NoAmmo => FireErrorSchema::NoAmmo,
+            FireError::NoEnergy => FireErrorSchema::NoEnergy,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RadarContactSchema {
+    object: String,
+    bearing: f64,
+    relative_bearing: f64,
     distance: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<usize>,
+}
+
+impl From<RadarContact> for RadarContactSchema {
+    fn from(value: RadarContact) -> Self {
+        let object = match value.target {
+            RadarTarget::Enemy => "ENEMY",
+            RadarTarget::Ally => "ALLY",
+            RadarTarget::Missile => "MISSILE",
+        };
+        RadarContactSchema {
+            object: object.to_string(),
+            bearing: value.bearing,
+            relative_bearing: value.relative_bearing,
+            distance: value.distance,
+            id: value.id,
+        }
+    }
 }
 
 #[derive(Serialize)]
 struct ViewSchema {
     view: Vec<ViewHitSchema>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rays: Option<Vec<RaySchema>>,
+    #[serde(rename = "self", skip_serializing_if = "Option::is_none")]
+    self_state: Option<SelfStateSchema>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    radar: Option<Vec<RadarContactSchema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fire_error: Option<FireErrorSchema>,
 }
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "action")]
 enum Action {
     Move { rotate: f64, speed: f64 },
+    Thrust { thrust: f64, turn: f64 },
     Fire,
 }
 
@@ -42,6 +193,8 @@ enum Action {
 enum PlayerStatusSchema {
     Killed { by: String },
     Win,
+    Lose,
+    Draw,
 }
 
 struct Connection(TcpStream);
@@ -76,25 +229,39 @@ impl Connection {
     }
 }
 
-fn make_rasponse_from_view(view: Vec<ViewHit>) -> ViewSchema {
-    ViewSchema {
-        view: view
+fn make_rasponse_from_view(
+    player: &Arc<Mutex<Player>>,
+    config: &Config,
+    fire_error: Option<FireError>,
+) -> ViewSchema {
+    let (view, rays) = if config.player_extended_view {
+        let rays = player.view_extended();
+        let view = rays.iter().map(|r| r.hit.into()).collect();
+        (view, Some(rays.into_iter().map(RaySchema::from).collect()))
+    } else {
+        (
+            player.view().into_iter().map(ViewHitSchema::from).collect(),
+            None,
+        )
+    };
+    let self_state = if config.player_self_state {
+        player.self_state().map(SelfStateSchema::from)
+    } else {
+        None
+    };
+    let radar = config.radar_radius.map(|_| {
+        player
+            .radar()
             .into_iter()
-            .map(|v| match v {
-                ViewHit::Barrier(d) => ViewHitSchema {
-                    object: "BARRIER".to_string(),
-                    distance: d,
-                },
-                ViewHit::Border(d) => ViewHitSchema {
-                    object: "BORDER".to_string(),
-                    distance: d,
-                },
-                ViewHit::Enemy(d) => ViewHitSchema {
-                    object: "ENEMY".to_string(),
-                    distance: d,
-                },
-            })
-            .collect(),
+            .map(RadarContactSchema::from)
+            .collect()
+    });
+    ViewSchema {
+        view,
+        rays,
+        self_state,
+        radar,
+        fire_error: fire_error.map(FireErrorSchema::from),
     }
 }
 
@@ -120,28 +287,30 @@ pub fn handle_stream(
     players_counter.store(players_counter_val + 1, Ordering::SeqCst);
     while players_counter.load(Ordering::SeqCst) != config.players_amount {}
 
-    let coordinates = game
-        .lock()
-        .unwrap()
-        .map
-        .get_free_point(config.player_radius);
-    let player = Player::new(
-        coordinates.0,
-        coordinates.1,
-        config.player_radius,
-        config.player_max_speed,
+    // The game picks the place on registration
+    let mut player = Player::from_config(
+        0.0,
+        0.0,
         config.player_view_angle,
         config.player_rays_amount,
-        config.player_missile_speed,
+        &config.game_config(),
     );
-    game.register_player(&player);
+    if let Some(teams_amount) = config.teams_amount {
+        // Players are dealt to teams in the order they join
+        player.set_team(players_counter_val % teams_amount + 1);
+    }
+    if let Err(e) = game.spawn_player(&player) {
+        error!(target: target, "Can't spawn the player: {}", e);
+        conn.close();
+        return Ok(());
+    }
     player_names
         .lock()
         .unwrap()
         .insert(player.get_id(), (player_name, ip.to_string()));
     info!(target: target, "Game started");
 
-    conn.send(make_rasponse_from_view(player.view()));
+    conn.send(make_rasponse_from_view(&player, &config, None));
 
     loop {
         let action = conn.receive::<Action>()?;
@@ -156,6 +325,16 @@ pub fn handle_stream(
                 conn.send(PlayerStatusSchema::Win);
                 break;
             }
+            PlayerStatus::Lose => {
+                info!(target: target, "Lose on time");
+                conn.send(PlayerStatusSchema::Lose);
+                break;
+            }
+            PlayerStatus::Draw => {
+                info!(target: target, "Draw on time");
+                conn.send(PlayerStatusSchema::Draw);
+                break;
+            }
             PlayerStatus::KilledBy(killer_id) => {
                 let locked_player_names = player_names.lock().unwrap();
                 let (killer_name, killer_ip) = locked_player_names.get(&killer_id).unwrap().clone();
@@ -167,19 +346,28 @@ pub fn handle_stream(
             _ => {}
         }
 
+        let mut fire_error = None;
         match action {
             Action::Fire => {
-                locked_player.fire();
+                if let Err(e) = locked_player.fire() {
+                    info!(target: target, "Fire rejected: {}", e);
+                    fire_error = Some(e);
+                }
             }
             Action::Move { rotate, speed } => {
                 info!(target: target, "Move rotate={}, speed={}", rotate, speed);
                 locked_player.rotate(rotate);
                 locked_player.set_speed(speed);
             }
+            Action::Thrust { thrust, turn } => {
+                info!(target: target, "Thrust thrust={}, turn={}", thrust, turn);
+                locked_player.set_thrust(thrust);
+                locked_player.set_turn(turn);
+            }
         }
         drop(locked_player);
 
-        conn.send(make_rasponse_from_view(player.view()));
+        conn.send(make_rasponse_from_view(&player, &config, fire_error));
 
         let now = SystemTime::now();
         let mut locked_last_processing_time = last_processing_time.lock().unwrap();
@@ -188,7 +376,13 @@ pub fn handle_stream(
         {
             let mut locked_game = game.lock().unwrap();
             locked_game.process(timedelta.unwrap_or(Duration::from_micros(1)).as_secs_f64());
-            history.lock().unwrap().write_state(&locked_game, &now);
+            let events = locked_game.drain_events();
+            for event in events.iter() {
+                debug!("{:.3}: {:?}", event.time, event.event);
+            }
+            let mut locked_history = history.lock().unwrap();
+            locked_history.write_state(&locked_game, &now);
+            locked_history.write_events(events);
         }
     }
 
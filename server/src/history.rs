@@ -2,11 +2,12 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::Serialize;
 
-use space_drive_game_core::{Game, Map};
+use space_drive_game_core::{Event, Game, GameEvent, Map, Shape};
 
 #[derive(Serialize)]
 struct Player {
     id: usize,
+    team: usize,
     ip: String,
     name: String,
 }
@@ -27,9 +28,162 @@ enum Object {
         r: f64,
         direction: f64,
         id: usize,
+        hp: f64,
+        shield: f64,
     },
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case", tag = "event")]
+enum EventKind {
+    ShotFired {
+        missile_id: usize,
+        player_id: usize,
+        x: f64,
+        y: f64,
+        direction: f64,
+    },
+    MissileExpired {
+        missile_id: usize,
+        x: f64,
+        y: f64,
+    },
+    MissileHitBorder {
+        missile_id: usize,
+        x: f64,
+        y: f64,
+    },
+    MissileHitBarrier {
+        missile_id: usize,
+        barrier: usize,
+        x: f64,
+        y: f64,
+    },
+    MissileIntercepted {
+        missile_id: usize,
+        other_id: usize,
+        x: f64,
+        y: f64,
+    },
+    PlayerHit {
+        missile_id: usize,
+        attacker_id: usize,
+        player_id: usize,
+        damage: f64,
+        x: f64,
+        y: f64,
+    },
+    PlayerKilled {
+        player_id: usize,
+        killer_id: usize,
+        x: f64,
+        y: f64,
+    },
+    ShipCollision {
+        player_id: usize,
+        other_id: usize,
+        x: f64,
+        y: f64,
+    },
+    GameOver {
+        winner_team: Option<usize>,
+    },
+}
+
+impl From<Event> for EventKind {
+    fn from(value: Event) -> Self {
+        match value {
+            Event::ShotFired {
+                missile_id,
+                player_id,
+                x,
+                y,
+                direction,
+            } => EventKind::ShotFired {
+                missile_id,
+                player_id,
+                x,
+                y,
+                direction,
+            },
+            Event::MissileExpired { missile_id, x, y } => {
+                EventKind::MissileExpired { missile_id, x, y }
+            }
+            Event::MissileHitBorder { missile_id, x, y } => {
+                EventKind::MissileHitBorder { missile_id, x, y }
+            }
+            Event::MissileHitBarrier {
+                missile_id,
+                barrier,
+                x,
+                y,
+            } => EventKind::MissileHitBarrier {
+                missile_id,
+                barrier,
+                x,
+                y,
+            },
+            Event::MissileIntercepted {
+                missile_id,
+                other_id,
+                x,
+                y,
+            } => EventKind::MissileIntercepted {
+                missile_id,
+                other_id,
+                x,
+                y,
+            },
+            Event::PlayerHit {
+                missile_id,
+                attacker_id,
+                player_id,
+                damage,
+                x,
+                y,
+            } => EventKind::PlayerHit {
+                missile_id,
+                attacker_id,
+                player_id,
+                damage,
+                x,
+                y,
+            },
+            Event::PlayerKilled {
+                player_id,
+                killer_id,
+                x,
+                y,
+            } => EventKind::PlayerKilled {
+                player_id,
+                killer_id,
+                x,
+                y,
+            },
+            Event::ShipCollision {
+                player_id,
+                other_id,
+                x,
+                y,
+            } => EventKind::ShipCollision {
+                player_id,
+                other_id,
+                x,
+                y,
+            },
+            Event::GameOver { winner_team } => EventKind::GameOver { winner_team },
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct EventState {
+    /// Game time, unlike the wall time of states
+    game_time: f64,
+    #[serde(flatten)]
+    event: EventKind,
+}
+
 #[derive(Serialize)]
 pub struct State {
     time: f64,
@@ -37,10 +191,64 @@ pub struct State {
 }
 
 #[derive(Serialize)]
-struct Barrier {
-    x: f64,
-    y: f64,
-    r: f64,
+#[serde(rename_all = "snake_case", tag = "shape")]
+enum Barrier {
+    Circle {
+        x: f64,
+        y: f64,
+        r: f64,
+    },
+    Rect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        angle: f64,
+    },
+    Polygon {
+        x: f64,
+        y: f64,
+        points: Vec<(f64, f64)>,
+    },
+    Capsule {
+        x: f64,
+        y: f64,
+        length: f64,
+        r: f64,
+        angle: f64,
+    },
+}
+
+impl From<&space_drive_game_core::map::Barrier> for Barrier {
+    fn from(value: &space_drive_game_core::map::Barrier) -> Self {
+        let (x, y) = (value.x, value.y);
+        match value.shape {
+            Shape::Circle { r } => Barrier::Circle { x, y, r },
+            Shape::Rect {
+                width,
+                height,
+                angle,
+            } => Barrier::Rect {
+                x,
+                y,
+                width,
+                height,
+                angle,
+            },
+            Shape::Polygon { ref points } => Barrier::Polygon {
+                x,
+                y,
+                points: points.clone(),
+            },
+            Shape::Capsule { length, r, angle } => Barrier::Capsule {
+                x,
+                y,
+                length,
+                r,
+                angle,
+            },
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -56,15 +264,7 @@ impl From<&Map> for MapState {
         MapState {
             width: value.width,
             height: value.height,
-            barriers: value
-                .barriers
-                .iter()
-                .map(|b| Barrier {
-                    x: b.x,
-                    y: b.y,
-                    r: b.r,
-                })
-                .collect(),
+            barriers: value.barriers.iter().map(Barrier::from).collect(),
             seed: value.seed,
         }
     }
@@ -73,9 +273,12 @@ impl From<&Map> for MapState {
 #[derive(Serialize)]
 pub struct History {
     map: MapState,
+    seed: u64,
     history: Vec<State>,
+    events: Vec<EventState>,
     players: Vec<Player>,
     winner: Option<Player>,
+    winner_team: Option<usize>,
     #[serde(skip_serializing)]
     optimization_rate: u8,
     #[serde(skip_serializing)]
@@ -83,12 +286,15 @@ pub struct History {
 }
 
 impl History {
-    pub fn new(map: &Map, optimization_rate: u8) -> Self {
+    pub fn new(game: &Game, optimization_rate: u8) -> Self {
         History {
-            map: map.into(),
+            map: (&game.map).into(),
+            seed: game.seed,
             history: Vec::new(),
+            events: Vec::new(),
             players: Vec::new(),
             winner: None,
+            winner_team: None,
             current_iteration: 0,
             optimization_rate,
         }
@@ -122,22 +328,38 @@ impl History {
                 r: locked_player.r,
                 direction: locked_player.direction,
                 id: locked_player.id,
+                hp: locked_player.health.hp,
+                shield: locked_player.health.shield,
             })
         }
         self.history.push(State { time, objects });
     }
 
-    pub fn add_player(&mut self, id: &usize, name: &str, ip: &str) {
+    /// Events are kept regardless of the optimization rate
+    pub fn write_events(&mut self, events: Vec<GameEvent>) {
+        self.events.extend(events.into_iter().map(|e| EventState {
+            game_time: e.time,
+            event: e.event.into(),
+        }));
+    }
+
+    pub fn set_winner_team(&mut self, team: usize) {
+        self.winner_team = Some(team);
+    }
+
+    pub fn add_player(&mut self, id: &usize, team: &usize, name: &str, ip: &str) {
         self.players.push(Player {
             id: *id,
+            team: *team,
             ip: ip.to_string(),
             name: name.to_string(),
         })
     }
 
-    pub fn set_winner(&mut self, id: &usize, name: &str, ip: &str) {
+    pub fn set_winner(&mut self, id: &usize, team: &usize, name: &str, ip: &str) {
         self.winner = Some(Player {
             id: *id,
+            team: *team,
             ip: ip.to_string(),
             name: name.to_string(),
         });
@@ -1,4 +1,4 @@
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use std::collections::HashMap;
 use std::env;
 use std::fs;
@@ -9,7 +9,8 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, SystemTime};
 
-use space_drive_game_core::{Game, Map};
+use space_drive_game_core::game::GameStatus;
+use space_drive_game_core::{ConfigError, Game, Map, MapFileError, MapReport, PlayerTrait};
 
 mod config;
 mod handler;
@@ -24,7 +25,13 @@ pub enum Error {
     #[error(transparent)]
     EnvError(#[from] envy::Error),
     #[error(transparent)]
+    InvalidConfigError(#[from] ConfigError),
+    #[error(transparent)]
     TCPListenerError(#[from] io::Error),
+    #[error(transparent)]
+    MapFileError(#[from] MapFileError),
+    #[error("map file has places players can't reach: {0:?}")]
+    InvalidMapError(MapReport),
 }
 
 fn main() -> Result<(), Error> {
@@ -42,26 +49,61 @@ fn main() -> Result<(), Error> {
     listener.set_nonblocking(true)?;
     info!("Server is running on {}", config.host);
 
-    let map = match config.map_seed {
-        Some(seed) => Map::new(
-            config.map_width,
-            config.map_height,
-            config.map_barriers_amount,
-            config.map_max_barrier_radius,
-            seed,
-        ),
-        None => Map::new_without_seed(
+    let map_seed = config.map_seed.unwrap_or_else(rand::random::<u64>);
+    let symmetry = config.map_symmetry.into();
+    let mut map = match (&config.map_file, symmetry) {
+        (Some(path), _) => {
+            // Hand-authored maps are never changed
+            let map = Map::from_file(path)?;
+            let report = map.validate(config.player_radius);
+            if !report.is_valid() {
+                return Err(Error::InvalidMapError(report));
+            }
+            map
+        }
+        (None, Some(symmetry)) => Map::new_symmetric(
             config.map_width,
             config.map_height,
             config.map_barriers_amount,
             config.map_max_barrier_radius,
+            map_seed,
+            symmetry,
+            config.players_amount,
         ),
+        (None, None) => match config.map_min_gap {
+            Some(min_gap) => Map::new_with_gaps(
+                config.map_width,
+                config.map_height,
+                config.map_barriers_amount,
+                config.map_max_barrier_radius,
+                map_seed,
+                min_gap,
+            ),
+            None => Map::new(
+                config.map_width,
+                config.map_height,
+                config.map_barriers_amount,
+                config.map_max_barrier_radius,
+                map_seed,
+            ),
+        },
     };
+    if config.map_repair && config.map_file.is_none() {
+        let removed = match symmetry {
+            Some(symmetry) => {
+                map.repair_symmetric(config.player_radius, symmetry, config.players_amount)
+            }
+            None => map.repair(config.player_radius),
+        };
+        if !removed.is_empty() {
+            warn!("Removed {} barriers walling off the map", removed.len());
+        }
+    }
+    let game = Game::new(map, config.game_config());
     let history = Arc::new(Mutex::new(History::new(
-        &map,
+        &game.lock().unwrap(),
         config.history_optimization_rate,
     )));
-    let game = Game::new(map);
     let last_processing_time = Arc::new(Mutex::new(SystemTime::now()));
     let player_names: Arc<Mutex<HashMap<usize, (String, String)>>> =
         Arc::new(Mutex::new(HashMap::new()));
@@ -118,13 +160,26 @@ fn main() -> Result<(), Error> {
     }
 
     info!("Sending history to the backend service");
+    // The game is locked before the history, as in the connection handlers
+    let locked_game = game.lock().unwrap();
+    let teams: HashMap<usize, usize> = locked_game
+        .players
+        .iter()
+        .map(|p| (p.get_id(), p.get_team()))
+        .collect();
+    let status = locked_game.status;
+    drop(locked_game);
     let data = &mut *history.lock().unwrap();
     let locked_player_names = player_names.lock().unwrap();
     let locked_winner_id = winner_id.lock().unwrap();
+    if let GameStatus::Over(team) = status {
+        data.set_winner_team(team);
+    }
     for (id, (name, ip)) in locked_player_names.iter() {
-        data.add_player(id, name, ip);
+        let team = teams.get(id).unwrap_or(id);
+        data.add_player(id, team, name, ip);
         if locked_winner_id.is_some() && &locked_winner_id.unwrap() == id {
-            data.set_winner(id, name, ip);
+            data.set_winner(id, team, name, ip);
         }
     }
     debug!("{}", serde_json::to_string(&data).unwrap());